@@ -1,12 +1,12 @@
 #[macro_use]
 extern crate criterion;
 
-use ecfft::{Coefficients, EcFft, Polynomial};
+use ecfft::{Coefficients, EcFft, Polynomial, Scratch};
 
 use pairing::bn256::Fq;
 use pairing::group::ff::Field;
 
-use criterion::{BenchmarkId, Criterion};
+use criterion::{BatchSize, BenchmarkId, Criterion};
 use rand_core::OsRng;
 
 fn criterion_benchmark(c: &mut Criterion) {
@@ -19,8 +19,82 @@ fn criterion_benchmark(c: &mut Criterion) {
             );
             b.iter(|| ecfft.evaluate(k, poly_a.clone()));
         });
+        enter_group.bench_function(BenchmarkId::new("scratch_k", k), |b| {
+            let poly_a = Polynomial::<Fq, Coefficients>::new(
+                (0..(1 << k)).map(|_| Fq::random(OsRng)).collect::<Vec<_>>(),
+            );
+            let mut scratch = Scratch::new(k);
+            b.iter(|| ecfft.evaluate_with_scratch(k, poly_a.clone(), &mut scratch));
+        });
+        enter_group.bench_function(BenchmarkId::new("layered_k", k), |b| {
+            let poly_a = Polynomial::<Fq, Coefficients>::new(
+                (0..(1 << k)).map(|_| Fq::random(OsRng)).collect::<Vec<_>>(),
+            );
+            b.iter(|| ecfft.evaluate_layered(k, poly_a.clone()));
+        });
     }
     enter_group.finish();
+
+    let mut many_group = c.benchmark_group("ecfft_many");
+    let m = 8;
+    for k in 10..15 {
+        let polys = (0..m)
+            .map(|_| {
+                Polynomial::<Fq, Coefficients>::new(
+                    (0..(1 << k)).map(|_| Fq::random(OsRng)).collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        many_group.bench_function(BenchmarkId::new("loop_k", k), |b| {
+            b.iter(|| {
+                polys
+                    .iter()
+                    .map(|poly| ecfft.evaluate(k, poly.clone()))
+                    .collect::<Vec<_>>()
+            });
+        });
+        many_group.bench_function(BenchmarkId::new("interleaved_k", k), |b| {
+            let coeffs = polys
+                .iter()
+                .map(|poly| poly.clone().get_values())
+                .collect::<Vec<_>>();
+            let values = (0..m << k)
+                .map(|i| coeffs[i % m][i / m])
+                .collect::<Vec<_>>();
+            b.iter(|| ecfft.evaluate_many_interleaved(k, m, &mut values.clone()));
+        });
+    }
+    many_group.finish();
+
+    let mut precompute_group = c.benchmark_group("ecfft_precompute");
+    precompute_group.sample_size(10);
+    precompute_group.bench_function("new", |b| b.iter(EcFft::new));
+    // params of 2^13 degree built from scratch or derived from the double degree ones
+    let k = 13;
+    precompute_group.bench_function("lazy_k13_from_scratch", |b| {
+        b.iter_batched(
+            EcFft::new_lazy,
+            |ecfft| ecfft.domain(k),
+            BatchSize::PerIteration,
+        )
+    });
+    precompute_group.bench_function("lazy_k13_half_sizing", |b| {
+        b.iter_batched(
+            || {
+                let ecfft = EcFft::new_lazy();
+                ecfft.domain(k + 1);
+                ecfft
+            },
+            |ecfft| ecfft.domain(k),
+            BatchSize::PerIteration,
+        )
+    });
+    let mut params = Vec::new();
+    ecfft.write_to(&mut params).unwrap();
+    precompute_group.bench_function("read_from", |b| {
+        b.iter(|| EcFft::read_from(&params[..]).unwrap())
+    });
+    precompute_group.finish();
 }
 
 criterion_group!(benches, criterion_benchmark);
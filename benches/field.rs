@@ -0,0 +1,83 @@
+#[macro_use]
+extern crate criterion;
+
+// the crate field is internal, so it is compiled into the bench as it is
+// its tests are compiled without a harness here, leaving their imports unused
+#[allow(dead_code, unused_imports)]
+#[path = "../src/ecfft/field.rs"]
+mod field;
+
+#[cfg(feature = "simd")]
+use field::Simd;
+use field::{sum_of_products, Fp};
+
+use pairing::bn256::Fq;
+use pairing::group::ff::Field;
+
+use criterion::{BenchmarkId, Criterion};
+use rand_core::OsRng;
+
+// (l, r) <- (f0 l + f1 r, f2 l + f3 r) as one layer of the ecfft matrix arithmetic
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut matrix_group = c.benchmark_group("field_matrix_arithmetic");
+    for k in [10, 14] {
+        let n = 1 << k;
+        let random = || (0..n).map(|_| Fq::random(OsRng)).collect::<Vec<_>>();
+        let (left, right) = (random(), random());
+        let factor = [random(), random(), random(), random()];
+        let internal = |values: &[Fq]| values.iter().map(|x| Fp::from(*x)).collect::<Vec<_>>();
+        let (left_fp, right_fp) = (internal(&left), internal(&right));
+        let factor_fp = [
+            internal(&factor[0]),
+            internal(&factor[1]),
+            internal(&factor[2]),
+            internal(&factor[3]),
+        ];
+
+        matrix_group.bench_function(BenchmarkId::new("pairing_k", k), |b| {
+            let (mut left, mut right) = (left.clone(), right.clone());
+            let [f0, f1, f2, f3] = &factor;
+            b.iter(|| {
+                for i in 0..n {
+                    let (l, r) = (left[i], right[i]);
+                    left[i] = f0[i] * l + f1[i] * r;
+                    right[i] = f2[i] * l + f3[i] * r;
+                }
+            });
+        });
+        matrix_group.bench_function(BenchmarkId::new("montgomery_k", k), |b| {
+            let (mut left, mut right) = (left_fp.clone(), right_fp.clone());
+            let [f0, f1, f2, f3] = &factor_fp;
+            b.iter(|| {
+                for i in 0..n {
+                    let (l, r) = (left[i], right[i]);
+                    left[i] = f0[i] * l + f1[i] * r;
+                    right[i] = f2[i] * l + f3[i] * r;
+                }
+            });
+        });
+        matrix_group.bench_function(BenchmarkId::new("sum_of_products_k", k), |b| {
+            let (mut left, mut right) = (left_fp.clone(), right_fp.clone());
+            let [f0, f1, f2, f3] = &factor_fp;
+            b.iter(|| {
+                for i in 0..n {
+                    let (l, r) = (left[i], right[i]);
+                    left[i] = sum_of_products(f0[i], l, f1[i], r);
+                    right[i] = sum_of_products(f2[i], l, f3[i], r);
+                }
+            });
+        });
+        #[cfg(feature = "simd")]
+        if let Some(simd) = Simd::detect() {
+            matrix_group.bench_function(BenchmarkId::new("avx2_k", k), |b| {
+                let (mut left, mut right) = (left_fp.clone(), right_fp.clone());
+                let [f0, f1, f2, f3] = &factor_fp;
+                b.iter(|| simd.matrix_arithmetic(&mut left, &mut right, [f0, f1, f2, f3]));
+            });
+        }
+    }
+    matrix_group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
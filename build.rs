@@ -0,0 +1,178 @@
+// generate ecfft params as static tables at build time when `precomputed` feature is enabled
+// the precomputation modules are compiled into this script as they are, so they must not refer
+// any module outside of `src/ecfft`, the shared points of `src/polynomial` and the field helpers
+// of `src/utils.rs` except in tests
+
+use pairing::group::ff::PrimeField;
+use std::env;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+#[allow(dead_code)]
+#[path = "src/ecfft"]
+mod ecfft {
+    mod arithmetic;
+    mod behave;
+    mod curve;
+    pub(crate) mod fftree;
+    pub(crate) mod field;
+    mod isogeny;
+    pub(crate) mod utils;
+
+    pub(crate) use curve::Ep;
+    use utils::EcFftCache;
+}
+
+#[allow(dead_code)]
+#[path = "src/utils.rs"]
+mod utils;
+
+#[allow(dead_code)]
+#[path = "src/polynomial"]
+mod polynomial {
+    mod shared;
+
+    pub(crate) use shared::SharedPoints;
+}
+
+use ecfft::fftree::{FactorMatrix, FfTree};
+use ecfft::utils::EcFftCache;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=src/ecfft");
+    println!("cargo:rerun-if-changed=src/polynomial/shared.rs");
+    println!("cargo:rerun-if-changed=src/utils.rs");
+
+    if env::var_os("CARGO_FEATURE_PRECOMPUTED").is_none() {
+        return;
+    }
+
+    let max_k = 14;
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let file = File::create(Path::new(&out_dir).join("ecfft_params.rs")).unwrap();
+    let caches = ecfft::utils::precompute_caches(max_k);
+    write_caches(&mut BufWriter::new(file), &caches).unwrap();
+}
+
+// static array of every table and caches referring them from 2^max_k degree down
+fn write_caches<W: Write>(out: &mut W, caches: &[EcFftCache]) -> io::Result<()> {
+    for cache in caches {
+        let k = cache.k;
+        write_table(out, &format!("COSET_{}", k), "Fq", &cache.coset)?;
+        write_table(
+            out,
+            &format!("POWERED_COSET_{}", k),
+            "Fp",
+            &cache.powered_coset,
+        )?;
+        for (depth, tree) in cache.trees.iter().enumerate() {
+            write_tree(out, &format!("TREE_{}_{}", k, depth), tree)?;
+        }
+
+        writeln!(
+            out,
+            "static TREES_{}: [FfTree; {}] = [",
+            k,
+            cache.trees.len()
+        )?;
+        for depth in 0..cache.trees.len() {
+            let name = format!("TREE_{}_{}", k, depth);
+            writeln!(out, "    FfTree {{")?;
+            writeln!(
+                out,
+                "        domain: (Cow::Borrowed(&{0}_S), Cow::Borrowed(&{0}_S_PRIME)),",
+                name
+            )?;
+            writeln!(
+                out,
+                "        factor: {},",
+                factor(&name, "FACTOR", &cache.trees[depth].factor)
+            )?;
+            writeln!(
+                out,
+                "        inv_factor: {},",
+                factor(&name, "INV_FACTOR", &cache.trees[depth].inv_factor)
+            )?;
+            writeln!(out, "    }},")?;
+        }
+        writeln!(out, "];")?;
+    }
+
+    writeln!(
+        out,
+        "pub(crate) static CACHES: [OnceCell<EcFftCache>; {}] = [",
+        caches.len()
+    )?;
+    for cache in caches {
+        writeln!(out, "    OnceCell::with_value(EcFftCache {{")?;
+        writeln!(out, "        k: {},", cache.k)?;
+        writeln!(out, "        trees: Cow::Borrowed(&TREES_{}),", cache.k)?;
+        writeln!(
+            out,
+            "        coset: SharedPoints::Static(&COSET_{}),",
+            cache.k
+        )?;
+        writeln!(
+            out,
+            "        powered_coset: Cow::Borrowed(&POWERED_COSET_{}),",
+            cache.k
+        )?;
+        writeln!(out, "    }}),")?;
+    }
+    writeln!(out, "];")
+}
+
+fn write_tree<W: Write>(out: &mut W, name: &str, tree: &FfTree) -> io::Result<()> {
+    let (s, s_prime) = &tree.domain;
+    write_table(out, &format!("{}_S", name), "Fp", s)?;
+    write_table(out, &format!("{}_S_PRIME", name), "Fp", s_prime)?;
+    for (label, matrix) in [("FACTOR", &tree.factor), ("INV_FACTOR", &tree.inv_factor)] {
+        let entries = match matrix {
+            FactorMatrix::Full(entries) => &entries[..],
+            FactorMatrix::Compact(scalars) => &scalars[..],
+        };
+        for (i, entry) in entries.iter().enumerate() {
+            write_table(out, &format!("{}_{}_{}", name, label, i), "Fp", entry)?;
+        }
+    }
+    Ok(())
+}
+
+// factor matrix expression borrowing its entry tables
+fn factor(name: &str, label: &str, matrix: &FactorMatrix) -> String {
+    let (layout, len) = match matrix {
+        FactorMatrix::Full(_) => ("Full", 4),
+        FactorMatrix::Compact(_) => ("Compact", 2),
+    };
+    let entries = (0..len)
+        .map(|i| format!("Cow::Borrowed(&{}_{}_{})", name, label, i))
+        .collect::<Vec<_>>();
+    format!("FactorMatrix::{}([{}])", layout, entries.join(", "))
+}
+
+// canonical limbs are converted at compile time by the const from_raw of the field, which is
+// the one of the pinned pairing tag for the public coset and the crate one for the others
+fn write_table<W: Write, F: PrimeField>(
+    out: &mut W,
+    name: &str,
+    field: &str,
+    table: &[F],
+) -> io::Result<()> {
+    writeln!(out, "static {}: [{}; {}] = [", name, field, table.len())?;
+    for element in table {
+        let repr = element.to_repr();
+        let limbs = repr
+            .as_ref()
+            .chunks(8)
+            .map(|limb| {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(limb);
+                format!("{:#018x}", u64::from_le_bytes(bytes))
+            })
+            .collect::<Vec<_>>();
+        writeln!(out, "    {}::from_raw([{}]),", field, limbs.join(", "))?;
+    }
+    writeln!(out, "];")
+}
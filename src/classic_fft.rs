@@ -1,5 +1,7 @@
 mod utils;
 
+use crate::polynomial::{short_multiply, Coefficients, Domain, Multiplier, PointValue, Polynomial};
+use crate::utils::batch_inversion;
 use utils::{butterfly_arithmetic, swap_bit_reverse};
 
 use pairing::arithmetic::*;
@@ -11,6 +13,8 @@ use rayon::{join, prelude::*};
 pub struct ClassicFft<G: Group> {
     // polynomial degree 2^k
     k: u32,
+    // generator of order 2^k multiplicative group which evaluations are on
+    omega: G::Scalar,
     // generator of order 2^{k - 1} multiplicative group used as twiddle factors
     twiddle_factors: Vec<G::Scalar>,
     // multiplicative group generator inverse
@@ -50,6 +54,7 @@ impl<G: Group> ClassicFft<G> {
 
         ClassicFft {
             k,
+            omega: multiplicative_generator,
             twiddle_factors,
             inv_twiddle_factors,
             n_inv,
@@ -80,6 +85,82 @@ impl<G: Group> ClassicFft<G> {
     }
 }
 
+impl<F: FieldExt> ClassicFft<F> {
+    // roots of unity which evaluations are on
+    pub fn domain(&self) -> Domain<F> {
+        Domain::RootsOfUnity(self.k as usize, self.omega)
+    }
+
+    // evaluate coefficients on roots of unity in order
+    pub fn evaluate(&self, coeffs: Polynomial<F, Coefficients>) -> Polynomial<F, PointValue> {
+        let mut values = coeffs.values;
+        self.dft(&mut values);
+        Polynomial::from_evaluations(values, self.domain())
+    }
+
+    // recover coefficients from evaluations on roots of unity
+    pub fn interpolate(&self, evals: Polynomial<F, PointValue>) -> Polynomial<F, Coefficients> {
+        assert!(
+            evals.domain() == &self.domain(),
+            "point values are not on roots of unity"
+        );
+        let mut values = evals.values;
+        self.idft(&mut values);
+        Polynomial::<F, Coefficients>::new(values)
+    }
+
+    // value at any point from evaluations on roots of unity
+    // (z^n - 1) / n * sum(v_i * w^i / (z - w^i)) in O(n)
+    pub fn evaluate_at(&self, evals: &Polynomial<F, PointValue>, z: F) -> F {
+        assert!(
+            evals.domain() == &self.domain(),
+            "point values are not on roots of unity"
+        );
+        let points = self.domain().points().into_owned();
+        let vanishing = z.pow_vartime([1u64 << self.k]) - F::one();
+        if vanishing == F::zero() {
+            let i = points.iter().position(|x| *x == z).unwrap();
+            return evals.values[i];
+        }
+
+        let mut inv_diffs = points.iter().map(|x| z - x).collect::<Vec<_>>();
+        batch_inversion(&mut inv_diffs);
+        let sum = inv_diffs
+            .iter()
+            .zip(points.iter())
+            .zip(evals.values.iter())
+            .fold(F::zero(), |acc, ((inv_diff, x), value)| {
+                acc + *inv_diff * x * value
+            });
+        sum * vanishing * self.n_inv
+    }
+}
+
+// multiplication by classic fft of the size of each product
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FftMultiplier;
+
+impl<F: FieldExt> Multiplier<F> for FftMultiplier {
+    fn multiply(&self, a: &[F], b: &[F]) -> Vec<F> {
+        if let Some(product) = short_multiply(a, b) {
+            return product;
+        }
+
+        let n = a.len() + b.len() - 1;
+        let classic_fft = ClassicFft::<F>::new(n.next_power_of_two().trailing_zeros());
+        let (mut a, mut b) = (a.to_vec(), b.to_vec());
+        a.resize(n.next_power_of_two(), F::zero());
+        b.resize(n.next_power_of_two(), F::zero());
+        join(|| classic_fft.dft(&mut a), || classic_fft.dft(&mut b));
+        a.par_iter_mut()
+            .zip(b.par_iter())
+            .for_each(|(a, b)| *a *= b);
+        classic_fft.idft(&mut a);
+        a.truncate(n);
+        a
+    }
+}
+
 // classic fft using divide and conquer algorithm
 fn classic_fft_arithmetic<G: Group>(
     coeffs: &mut [G],
@@ -104,7 +185,8 @@ fn classic_fft_arithmetic<G: Group>(
 
 #[cfg(test)]
 mod tests {
-    use super::ClassicFft;
+    use super::{ClassicFft, FftMultiplier};
+    use crate::polynomial::{Coefficients, Karatsuba, Multiplier, Polynomial};
     use pairing::bn256::Fr as Fp;
     use pairing::group::ff::Field;
     use proptest::prelude::*;
@@ -114,6 +196,41 @@ mod tests {
         (0..(1 << k)).map(|_| Fp::random(OsRng)).collect::<Vec<_>>()
     }
 
+    #[test]
+    fn test_evaluate_and_interpolate() {
+        for k in 1..8 {
+            let classic_fft = ClassicFft::<Fp>::new(k);
+            let poly = Polynomial::<Fp, Coefficients>::new(arb_poly(k));
+            let points = classic_fft.domain().points().to_vec();
+
+            let evals = classic_fft.evaluate(poly.clone());
+            assert_eq!(evals, poly.to_point_value(&points));
+            assert_eq!(evals.points(), points);
+            let z = Fp::random(OsRng);
+            assert_eq!(
+                classic_fft.evaluate_at(&evals, z),
+                poly.clone().polynomial_evaluation(z)
+            );
+            assert_eq!(classic_fft.evaluate_at(&evals, points[1]), evals.values[1]);
+            assert_eq!(classic_fft.interpolate(evals), poly);
+        }
+    }
+
+    #[test]
+    fn test_fft_multiplier() {
+        for (k, m) in [(0, 3), (6, 6), (7, 5), (10, 9)] {
+            let (a, b) = (arb_poly(k), arb_poly(m));
+            assert_eq!(FftMultiplier.multiply(&a, &b), Karatsuba.multiply(&a, &b));
+        }
+
+        // half gcd over the scalar field
+        let poly = |k| Polynomial::<Fp, Coefficients>::new(arb_poly(k));
+        let factor = poly(5);
+        let a = factor.clone().naive_multiply(poly(9));
+        let b = factor.naive_multiply(poly(8));
+        assert_eq!(a.fast_xgcd(&b, &FftMultiplier), a.xgcd(&b));
+    }
+
     proptest! {
         #![proptest_config(ProptestConfig::with_cases(100))]
         #[test]
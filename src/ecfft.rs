@@ -1,76 +1,392 @@
 mod arithmetic;
 mod behave;
 mod curve;
+#[cfg(feature = "precomputed")]
+mod embedded;
+mod exit;
+mod external;
 mod fftree;
+mod field;
 mod isogeny;
+mod serialize;
 mod utils;
+mod verify;
 
-use crate::polynomial::{Coefficients, PointValue, Polynomial};
-use arithmetic::{parallel_integrate_evaluation, serial_integrate_evaluation};
+use crate::polynomial::{Coefficients, Domain, PointValue, Polynomial};
+use crate::utils::parallel_batch_inversion;
+use arithmetic::{
+    bottom_layer_conversion, exceeds_cutoff, layer_conversion, layer_integrate_evaluation,
+    parallel_integrate_evaluation, parallel_interleaved_integrate_evaluation,
+    parallel_interleaved_low_degree_extention, parallel_single_low_degree_extention, pow_small,
+    serial_integrate_evaluation, serial_interleaved_integrate_evaluation,
+    serial_interleaved_low_degree_extention,
+};
 pub(crate) use curve::Ep;
-use utils::EcFftCache;
+pub use exit::EcFftMultiplier;
+use fftree::Extension;
+pub(crate) use field::Fp;
+pub(crate) use utils::generate_coset;
+use utils::{Derived, EcFftCache};
+pub use verify::{VerifyReport, Violation};
 
-use pairing::bn256::Fq as Fp;
-use rayon::{current_num_threads, join};
-use std::marker::PhantomData;
+use once_cell::sync::OnceCell;
+use pairing::bn256::Fq;
+use pairing::group::ff::Field;
+use rayon::{join, prelude::*, ThreadPool};
+use std::io::{self, Read, Write};
+use std::ops::Deref;
+use std::sync::Arc;
 
-// precomputed params for ecfft
+// polynomials of 2^k degree up to this are evaluated on one thread
+const DEFAULT_PARALLEL_CUTOFF: usize = 10;
+
+// process-wide params shared by every handle
+static GLOBAL_ECFFT: once_cell::sync::Lazy<EcFft> = once_cell::sync::Lazy::new(EcFft::new);
+
+// precomputed params for ecfft, cloning shares the params
 #[derive(Clone, Debug)]
 pub struct EcFft {
     // polynomial degree 2^k
     max_k: usize,
-    // precomputed ecfft params
-    caches: Vec<EcFftCache>,
+    // precomputed ecfft params initialised on first use
+    caches: Params,
+    // data derived from params of each degree on first use, shared by clones as well
+    derived: Arc<Vec<Derived>>,
+    // log size of subproblems which are no longer split across threads
+    parallel_cutoff: usize,
+}
+
+// params of every degree from 2^max_k down to 2
+// embedded ones are referred in place and the others are shared behind an arc
+#[derive(Clone, Debug)]
+enum Params {
+    #[cfg(feature = "precomputed")]
+    Static(&'static [OnceCell<EcFftCache>]),
+    Shared(Arc<Vec<OnceCell<EcFftCache>>>),
+}
+
+impl Deref for Params {
+    type Target = [OnceCell<EcFftCache>];
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            #[cfg(feature = "precomputed")]
+            Params::Static(caches) => caches,
+            Params::Shared(caches) => caches,
+        }
+    }
 }
 
 impl EcFft {
+    #[cfg(not(feature = "precomputed"))]
+    pub fn new() -> Self {
+        let max_k = 14;
+        Self::from_caches(max_k, utils::precompute_caches(max_k))
+    }
+
+    // refer static params generated by build script
+    #[cfg(feature = "precomputed")]
     pub fn new() -> Self {
+        EcFft {
+            max_k: embedded::CACHES.len(),
+            caches: Params::Static(&embedded::CACHES),
+            derived: Arc::new(Derived::levels(embedded::CACHES.len())),
+            parallel_cutoff: DEFAULT_PARALLEL_CUTOFF,
+        }
+    }
+
+    // process-wide ecfft initialised on first use
+    pub fn global() -> &'static Self {
+        &GLOBAL_ECFFT
+    }
+
+    // ecfft keeping two scalars of each factor matrix which roughly halves the factor tables
+    pub fn new_compact() -> Self {
+        let ecfft = Self::new();
+        let caches = (0..ecfft.max_k)
+            .map(|i| ecfft.get_cache(ecfft.max_k - i).clone().compact())
+            .collect();
+        Self::from_caches(ecfft.max_k, caches)
+    }
+
+    // ecfft whose params are precomputed when the polynomial degree is used at first
+    pub fn new_lazy() -> Self {
         let max_k = 14;
-        let n = 1 << max_k;
-        let acc = Ep::generator();
-        let presentative = Ep::representative();
-        let mut caches = Vec::new();
-        let mut coset = (0..n)
-            .map(|i| {
-                let coset_point = presentative + acc * Fp::from_raw([i, 0, 0, 0]);
-                coset_point.to_affine().point_projective()
+        let caches = (0..max_k).map(|_| OnceCell::new()).collect();
+
+        EcFft {
+            max_k,
+            caches: Params::Shared(Arc::new(caches)),
+            derived: Arc::new(Derived::levels(max_k)),
+            parallel_cutoff: DEFAULT_PARALLEL_CUTOFF,
+        }
+    }
+
+    // polynomials and subproblems of 2^cutoff or smaller size are processed serially
+    pub fn set_parallel_cutoff(&mut self, cutoff: usize) {
+        self.parallel_cutoff = cutoff;
+    }
+
+    // memory footprint in bytes of initialised params as (k, bytes)
+    pub fn memory_footprint(&self) -> Vec<(usize, usize)> {
+        (1..=self.max_k)
+            .filter_map(|k| {
+                self.caches[self.max_k - k]
+                    .get()
+                    .map(|cache| (k, cache.memory_size()))
             })
-            .collect::<Vec<_>>();
+            .collect()
+    }
+
+    // memory footprint in bytes of weights and interpolation params derived so far as (k, bytes)
+    pub fn derived_footprint(&self) -> Vec<(usize, usize)> {
+        (1..=self.max_k)
+            .map(|k| (k, self.derived[self.max_k - k].memory_size()))
+            .filter(|(_, size)| *size > 0)
+            .collect()
+    }
+
+    // check invariants of every level params initialising lazy ones
+    pub fn verify(&self) -> VerifyReport {
+        let checked = (1..=self.max_k).collect::<Vec<_>>();
+        let violations = checked
+            .iter()
+            .flat_map(|k| self.get_cache(*k).verify())
+            .collect();
 
-        for i in 0..max_k {
-            let cache = EcFftCache::new(max_k - i, coset.clone());
-            caches.push(cache);
-            coset = coset.into_iter().step_by(2).collect();
+        VerifyReport {
+            checked,
+            violations,
         }
+    }
+
+    // write precomputed params with versioned layout and checksum
+    pub fn write_to<W: Write>(&self, writer: W) -> io::Result<()> {
+        let caches = (0..self.max_k)
+            .map(|i| self.get_cache(self.max_k - i))
+            .collect::<Vec<_>>();
+        serialize::write_caches(writer, self.max_k, caches)
+    }
 
-        EcFft { max_k, caches }
+    // read precomputed params rejecting corrupted or mismatched one
+    pub fn read_from<R: Read>(reader: R) -> io::Result<Self> {
+        let (max_k, caches) = serialize::read_caches(reader)?;
+        Ok(Self::from_caches(max_k, caches))
+    }
+
+    fn from_caches(max_k: usize, caches: Vec<EcFftCache>) -> Self {
+        let caches = caches
+            .into_iter()
+            .map(|cache| {
+                let cell = OnceCell::new();
+                cell.set(cache).unwrap();
+                cell
+            })
+            .collect();
+        EcFft {
+            max_k,
+            caches: Params::Shared(Arc::new(caches)),
+            derived: Arc::new(Derived::levels(max_k)),
+            parallel_cutoff: DEFAULT_PARALLEL_CUTOFF,
+        }
     }
 
     pub fn evaluate(
         &self,
         k: usize,
-        mut coeffs: Polynomial<Fp, Coefficients>,
-    ) -> Polynomial<Fp, PointValue> {
+        coeffs: Polynomial<Fq, Coefficients>,
+    ) -> Polynomial<Fq, PointValue> {
+        self.evaluate_with_scratch(k, coeffs, &mut Scratch::new(k))
+    }
+
+    // evaluate reusing temporary buffers of scratch across calls
+    pub fn evaluate_with_scratch(
+        &self,
+        k: usize,
+        mut coeffs: Polynomial<Fq, Coefficients>,
+        scratch: &mut Scratch,
+    ) -> Polynomial<Fq, PointValue> {
         assert!(k <= self.max_k);
-        let thread_num = current_num_threads();
-        let thread_log = thread_num;
-        let is_parallel = k > thread_log;
+        assert_eq!(coeffs.values.len(), 1 << k);
+        let (values, scratch) = scratch.get_mut(k);
+        convert(&coeffs.values, values);
+        self.par_enter(values, scratch, k);
+        convert(values, &mut coeffs.values);
 
-        if is_parallel {
-            self.par_enter(&mut coeffs.values, k, thread_log);
+        Polynomial::from_evaluations(coeffs.values, self.domain(k))
+    }
+
+    // evaluate on the given thread pool instead of the global one
+    pub fn evaluate_in(
+        &self,
+        pool: &ThreadPool,
+        k: usize,
+        coeffs: Polynomial<Fq, Coefficients>,
+    ) -> Polynomial<Fq, PointValue> {
+        pool.install(|| self.evaluate(k, coeffs))
+    }
+
+    // evaluate bottom up processing every node of the same degree in one pass
+    // instead of recursing depth first
+    pub fn evaluate_layered(
+        &self,
+        k: usize,
+        mut coeffs: Polynomial<Fq, Coefficients>,
+    ) -> Polynomial<Fq, PointValue> {
+        assert!(k <= self.max_k);
+        let mut values = internal(&coeffs.values);
+        let mut snapshot = vec![Fp::zero(); values.len()];
+        let mut out = vec![Fp::zero(); values.len()];
+
+        let powered_coset = &self.get_cache(1).powered_coset;
+        values.par_chunks_mut(2).for_each(|coeffs| {
+            let (a, b) = (powered_coset[0] * coeffs[1], powered_coset[1] * coeffs[1]);
+            coeffs[1] = coeffs[0];
+            coeffs[0] += a;
+            coeffs[1] += b;
+        });
+
+        for m in 2..=k {
+            let cache = self.get_cache(m);
+            snapshot.copy_from_slice(&values);
+
+            // extend every half of 2^m size nodes from s to s_prime
+            for depth in 0..(m - 2) {
+                layer_conversion(&mut values, cache.get_tree(depth).get_inv_factor());
+            }
+            bottom_layer_conversion(&mut values, cache.get_last_tree());
+            for depth in (0..(m - 2)).rev() {
+                layer_conversion(&mut values, cache.get_tree(depth).get_factor());
+            }
+
+            layer_integrate_evaluation(&mut out, &snapshot, &values, &cache.powered_coset);
+            std::mem::swap(&mut values, &mut out);
+        }
+
+        convert(&values, &mut coeffs.values);
+        Polynomial::from_evaluations(coeffs.values, self.domain(k))
+    }
+
+    // evaluate m polynomials of 2^k degree stored interleaved in place
+    // the i-th coefficient of the j-th polynomial is values[i * m + j] and so are evaluations
+    // each factor matrix and power of coset is loaded once for all polynomials
+    pub fn evaluate_many_interleaved(&self, k: usize, m: usize, values: &mut [Fq]) {
+        assert!((1..=self.max_k).contains(&k));
+        assert!(m > 0);
+        assert_eq!(values.len(), m << k);
+        let mut coeffs = internal(values);
+        let mut scratch = vec![Fp::zero(); values.len() << 1];
+        self.interleaved_enter(&mut coeffs, &mut scratch, k, m);
+        convert(&coeffs, values);
+    }
+
+    // evaluate skipping subtrees whose coefficients are all zero
+    // a polynomial of low degree costs about as much as evaluating on a domain of its degree
+    pub fn evaluate_pruned(
+        &self,
+        k: usize,
+        mut coeffs: Polynomial<Fq, Coefficients>,
+    ) -> Polynomial<Fq, PointValue> {
+        assert!((1..=self.max_k).contains(&k));
+        let mut values = internal(&coeffs.values);
+        let mut scratch = vec![Fp::zero(); 1 << (k + 1)];
+        self.pruned_enter(&mut values, &mut scratch, k);
+        convert(&values, &mut coeffs.values);
+
+        Polynomial::from_evaluations(coeffs.values, self.domain(k))
+    }
+
+    // evaluations of the given indices on the coset in the same order
+    pub fn evaluate_partial(
+        &self,
+        k: usize,
+        coeffs: Polynomial<Fq, Coefficients>,
+        indices: &[usize],
+    ) -> Vec<Fq> {
+        assert!((1..=self.max_k).contains(&k));
+        assert_eq!(coeffs.values.len(), 1 << k);
+        assert!(indices.iter().all(|index| *index < 1 << k));
+        let values = self.partial_enter(&internal(&coeffs.values), k, indices);
+        values.into_iter().map(Fq::from).collect()
+    }
+
+    // patch evaluations on the coset after adding delta to each coefficient of index
+    // every point adds the sparse polynomial of changes
+    pub fn update_evaluations(
+        &self,
+        k: usize,
+        evals: &mut Polynomial<Fq, PointValue>,
+        changes: &[(usize, Fq)],
+    ) {
+        assert!((1..=self.max_k).contains(&k));
+        assert_eq!(evals.values.len(), 1 << k);
+        assert!(evals.domain() == &self.domain(k));
+        assert!(changes.iter().all(|(index, _)| *index < 1 << k));
+        if changes.is_empty() {
+            return;
+        }
+
+        let mut changes = changes.to_vec();
+        changes.sort_unstable_by_key(|(index, _)| *index);
+        // powers of each point are raised incrementally from the lowest index
+        // and each step costs the bit length of the gap
+        let update = |eval: &mut Fq, x: &Fq| {
+            let (mut power, mut prev) = (Fq::one(), 0);
+            for (index, delta) in changes.iter() {
+                power *= pow_small(*x, index - prev);
+                *eval += power * delta;
+                prev = *index;
+            }
+        };
+
+        let coset = &self.get_cache(k).coset;
+        if k > self.parallel_cutoff {
+            evals
+                .values
+                .par_iter_mut()
+                .zip(coset.par_iter())
+                .for_each(|(eval, x)| update(eval, x));
         } else {
-            self.enter(&mut coeffs.values, k);
+            evals
+                .values
+                .iter_mut()
+                .zip(coset.iter())
+                .for_each(|(eval, x)| update(eval, x));
         }
+    }
 
-        Polynomial {
-            values: coeffs.values,
-            _marker: PhantomData,
+    // value at any point from evaluations on the coset by the barycentric formula
+    // sum(w_i * v_i / (z - x_i)) / sum(w_i / (z - x_i)) in O(n) with weights of the params
+    pub fn evaluate_at(&self, k: usize, evals: &Polynomial<Fq, PointValue>, z: Fq) -> Fq {
+        assert!((1..=self.max_k).contains(&k));
+        assert!(
+            evals.domain() == &self.domain(k),
+            "point values are not on the ecfft coset"
+        );
+        let cache = self.get_cache(k);
+        if let Some(i) = cache.coset.par_iter().position_any(|x| *x == z) {
+            return evals.values[i];
         }
+
+        let mut inv_diffs = cache.coset.par_iter().map(|x| z - x).collect::<Vec<_>>();
+        parallel_batch_inversion(&mut inv_diffs);
+        let (numerator, denominator) = inv_diffs
+            .par_iter()
+            .zip(self.get_barycentric_weights(k).par_iter())
+            .zip(evals.values.par_iter())
+            .map(|((inv_diff, weight), value)| {
+                let term = *inv_diff * weight;
+                (term * value, term)
+            })
+            .reduce(|| (Fq::zero(), Fq::zero()), |(a, b), (c, d)| (a + c, b + d));
+        numerator * denominator.invert().unwrap()
     }
 
-    fn enter(&self, coeffs: &mut [Fp], k: usize) {
+    // scratch has 2^{k+1} size, half for each child while recursing
+    // and snapshot and output of integration afterwards
+    fn enter(&self, coeffs: &mut [Fp], scratch: &mut [Fp], k: usize) {
         if k == 1 {
-            let cache = &self.caches[self.max_k - k];
+            let cache = self.get_cache(k);
             let (a, b) = (
                 cache.powered_coset[0] * coeffs[1],
                 cache.powered_coset[1] * coeffs[1],
@@ -81,94 +397,606 @@ impl EcFft {
             return;
         }
 
+        let n = 1 << k;
         let next_k = k - 1;
-        let (low, high) = coeffs.split_at_mut(1 << next_k);
+        let (low, high) = coeffs.split_at_mut(n >> 1);
+        let (low_scratch, high_scratch) = scratch.split_at_mut(n);
 
-        join(|| self.enter(low, next_k), || self.enter(high, next_k));
+        self.enter(low, low_scratch, next_k);
+        self.enter(high, high_scratch, next_k);
 
-        let (low_prime, high_prime) = (low.to_vec(), high.to_vec());
-        let cache = &self.caches[self.max_k - k];
+        let (snapshot, out) = scratch.split_at_mut(n);
+        snapshot[..n >> 1].copy_from_slice(low);
+        snapshot[n >> 1..].copy_from_slice(high);
+        let cache = self.get_cache(k);
         cache.extend(low, high, k);
-        let (low, high) = (low.to_vec(), high.to_vec());
-
-        serial_integrate_evaluation(
-            coeffs,
-            low_prime,
-            high_prime,
-            low,
-            high,
-            &cache.powered_coset,
-        );
+        let (low_prime, high_prime) = snapshot.split_at(n >> 1);
+
+        serial_integrate_evaluation(out, low_prime, high_prime, low, high, &cache.powered_coset);
+        coeffs.copy_from_slice(out);
     }
 
-    fn par_enter(&self, coeffs: &mut [Fp], k: usize, thread_log: usize) {
-        if k == 1 {
-            let powered_coset = &self.caches[self.max_k - k].powered_coset;
-            let (a, b) = (powered_coset[0] * coeffs[1], powered_coset[1] * coeffs[1]);
-            coeffs[1] = coeffs[0];
-            coeffs[0] += a;
-            coeffs[1] += b;
+    // split subproblems across threads until they reach the cutoff size
+    fn par_enter(&self, coeffs: &mut [Fp], scratch: &mut [Fp], k: usize) {
+        if k <= self.parallel_cutoff.max(1) {
+            self.enter(coeffs, scratch, k);
             return;
         }
 
+        let n = 1 << k;
         let next_k = k - 1;
-        let (low, high) = coeffs.split_at_mut(1 << next_k);
-        let cache = &self.caches[self.max_k - k];
+        let (low, high) = coeffs.split_at_mut(n >> 1);
+        let (low_scratch, high_scratch) = scratch.split_at_mut(n);
+        let cache = self.get_cache(k);
 
         join(
-            || self.par_enter(low, next_k, thread_log),
-            || self.par_enter(high, next_k, thread_log),
+            || self.par_enter(low, low_scratch, next_k),
+            || self.par_enter(high, high_scratch, next_k),
         );
 
-        let (low_prime, high_prime) = (low.to_vec(), high.to_vec());
-        cache.par_extend(low, high, k);
-        let (low, high) = (low.to_vec(), high.to_vec());
-
-        serial_integrate_evaluation(
-            coeffs,
-            low_prime,
-            high_prime,
-            low,
-            high,
-            &cache.powered_coset,
-        );
+        let (snapshot, out) = scratch.split_at_mut(n);
+        snapshot[..n >> 1].copy_from_slice(low);
+        snapshot[n >> 1..].copy_from_slice(high);
+        cache.par_extend(low, high, k, self.parallel_cutoff);
+        let (low_prime, high_prime) = snapshot.split_at(n >> 1);
+
+        parallel_integrate_evaluation(out, low_prime, high_prime, low, high, &cache.powered_coset);
+        coeffs.copy_from_slice(out);
     }
 
-    #[cfg(test)]
-    pub(crate) fn get_cache(&self, k: usize) -> EcFftCache {
-        self.caches[self.max_k - k].clone()
+    // enter which returns zero evaluations of zero nodes as they are
+    // and extends only the low half of nodes whose high half is zero
+    fn pruned_enter(&self, coeffs: &mut [Fp], scratch: &mut [Fp], k: usize) {
+        if is_zero(coeffs) {
+            return;
+        }
+        if k == 1 {
+            self.enter(coeffs, scratch, k);
+            return;
+        }
+
+        let n = 1 << k;
+        let next_k = k - 1;
+        let (low, high) = coeffs.split_at_mut(n >> 1);
+        let (low_scratch, high_scratch) = scratch.split_at_mut(n);
+        let cache = self.get_cache(k);
+
+        if is_zero(high) {
+            self.pruned_enter(low, low_scratch, next_k);
+            // p(x) = low(x) so even points are low on s and odd ones low extended on s_prime
+            let (snapshot, extended) = scratch[..n].split_at_mut(n >> 1);
+            snapshot.copy_from_slice(low);
+            parallel_single_low_degree_extention(low, k, 0, self.parallel_cutoff, cache);
+            extended.copy_from_slice(low);
+            coeffs
+                .chunks_mut(2)
+                .zip(snapshot.iter().zip(extended.iter()))
+                .for_each(|(points, (even, odd))| {
+                    points[0] = *even;
+                    points[1] = *odd;
+                });
+            return;
+        }
+
+        if k > self.parallel_cutoff {
+            join(
+                || self.pruned_enter(low, low_scratch, next_k),
+                || self.pruned_enter(high, high_scratch, next_k),
+            );
+        } else {
+            self.pruned_enter(low, low_scratch, next_k);
+            self.pruned_enter(high, high_scratch, next_k);
+        }
+
+        let (snapshot, out) = scratch.split_at_mut(n);
+        snapshot[..n >> 1].copy_from_slice(low);
+        snapshot[n >> 1..].copy_from_slice(high);
+        cache.par_extend(low, high, k, self.parallel_cutoff);
+        let (low_prime, high_prime) = snapshot.split_at(n >> 1);
+
+        parallel_integrate_evaluation(out, low_prime, high_prime, low, high, &cache.powered_coset);
+        coeffs.copy_from_slice(out);
+    }
+
+    // even points of a node only need the evaluations of the same half index of its children
+    // so they are recursed, and the others are evaluated directly if they are few
+    fn partial_enter(&self, coeffs: &[Fp], k: usize, indices: &[usize]) -> Vec<Fp> {
+        let cache = self.get_cache(k);
+
+        if k > 1 && indices.iter().all(|index| index % 2 == 0) {
+            let (low, high) = coeffs.split_at(1 << (k - 1));
+            let half_indices = indices.iter().map(|index| index >> 1).collect::<Vec<_>>();
+            let (low, high) = if k > self.parallel_cutoff {
+                join(
+                    || self.partial_enter(low, k - 1, &half_indices),
+                    || self.partial_enter(high, k - 1, &half_indices),
+                )
+            } else {
+                (
+                    self.partial_enter(low, k - 1, &half_indices),
+                    self.partial_enter(high, k - 1, &half_indices),
+                )
+            };
+            return indices
+                .iter()
+                .zip(low.iter().zip(high.iter()))
+                .map(|(index, (low, high))| low + cache.powered_coset[*index] * high)
+                .collect();
+        }
+
+        // the whole evaluation costs about k^2 multiplications per point
+        if indices.len() < k * k {
+            let zeros = coeffs
+                .iter()
+                .rev()
+                .take_while(|coeff| **coeff == Fp::zero());
+            let degree = coeffs.len() - zeros.count();
+            let horner = |index: &usize| {
+                let x = Fp::from(cache.coset[*index]);
+                coeffs[..degree]
+                    .iter()
+                    .rev()
+                    .fold(Fp::zero(), |acc, coeff| acc * x + coeff)
+            };
+            return if k > self.parallel_cutoff {
+                indices.par_iter().map(horner).collect()
+            } else {
+                indices.iter().map(horner).collect()
+            };
+        }
+
+        let mut values = coeffs.to_vec();
+        let mut scratch = vec![Fp::zero(); 1 << (k + 1)];
+        self.pruned_enter(&mut values, &mut scratch, k);
+        indices.iter().map(|index| values[*index]).collect()
+    }
+
+    // enter on m interleaved polynomials with the same scratch layout
+    // nodes larger than 2^cutoff in total are split across threads
+    fn interleaved_enter(&self, coeffs: &mut [Fp], scratch: &mut [Fp], k: usize, m: usize) {
+        let cache = self.get_cache(k);
+        if k == 1 {
+            let (low, high) = coeffs.split_at_mut(m);
+            low.iter_mut().zip(high.iter_mut()).for_each(|(a, b)| {
+                let (c, d) = (cache.powered_coset[0] * *b, cache.powered_coset[1] * *b);
+                *b = *a + d;
+                *a += c;
+            });
+            return;
+        }
+
+        let n = coeffs.len();
+        let half_n = 1 << (k - 1);
+        let is_parallel = exceeds_cutoff(n, self.parallel_cutoff);
+        let (low, high) = coeffs.split_at_mut(n >> 1);
+        let (low_scratch, high_scratch) = scratch.split_at_mut(n);
+
+        if is_parallel {
+            join(
+                || self.interleaved_enter(low, low_scratch, k - 1, m),
+                || self.interleaved_enter(high, high_scratch, k - 1, m),
+            );
+        } else {
+            self.interleaved_enter(low, low_scratch, k - 1, m);
+            self.interleaved_enter(high, high_scratch, k - 1, m);
+        }
+
+        let (snapshot, out) = scratch.split_at_mut(n);
+        snapshot[..n >> 1].copy_from_slice(low);
+        snapshot[n >> 1..].copy_from_slice(high);
+        let (low_prime, high_prime) = snapshot.split_at(n >> 1);
+        let powered_coset = &cache.powered_coset;
+
+        if is_parallel {
+            let cutoff = self.parallel_cutoff;
+            parallel_interleaved_low_degree_extention(low, high, half_n, k, 0, m, cutoff, cache);
+            parallel_interleaved_integrate_evaluation(
+                out,
+                low_prime,
+                high_prime,
+                low,
+                high,
+                powered_coset,
+                m,
+            );
+        } else {
+            serial_interleaved_low_degree_extention(low, high, half_n, k, 0, m, cache);
+            serial_interleaved_integrate_evaluation(
+                out,
+                low_prime,
+                high_prime,
+                low,
+                high,
+                powered_coset,
+                m,
+            );
+        }
+        coeffs.copy_from_slice(out);
+    }
+
+    // coset of 2^k size which evaluations are on
+    pub fn domain(&self, k: usize) -> Domain<Fq> {
+        Domain::EcFftCoset(k, self.get_cache(k).coset.clone())
+    }
+
+    // barycentric weights of the coset of 2^k size computing them if they are not yet
+    fn get_barycentric_weights(&self, k: usize) -> &[Fq] {
+        self.get_derived(k)
+            .barycentric_weights
+            .get_or_init(|| self.get_cache(k).barycentric_weights())
+    }
+
+    // data derived from params of 2^k degree
+    pub(crate) fn get_derived(&self, k: usize) -> &Derived {
+        assert!((1..=self.max_k).contains(&k));
+        &self.derived[self.max_k - k]
+    }
+
+    // get params of 2^k degree initialising them if they are not yet
+    pub(crate) fn get_cache(&self, k: usize) -> &EcFftCache {
+        assert!((1..=self.max_k).contains(&k));
+        let depth = self.max_k - k;
+        self.caches[depth].get_or_init(|| {
+            // derive from double degree params if they are already initialised
+            match depth.checked_sub(1).and_then(|i| self.caches[i].get()) {
+                Some(cache) => cache.half_sizing(),
+                None => EcFftCache::new(k, generate_coset(self.max_k, depth)),
+            }
+        })
+    }
+}
+
+fn is_zero(values: &[Fp]) -> bool {
+    values.iter().all(|value| *value == Fp::zero())
+}
+
+// values of the public field in the internal one
+fn internal(values: &[Fq]) -> Vec<Fp> {
+    values.par_iter().map(|value| Fp::from(*value)).collect()
+}
+
+// copy values between the public and the internal field
+fn convert<A, B>(values: &[A], out: &mut [B])
+where
+    A: Copy + Sync,
+    B: From<A> + Send,
+{
+    out.par_iter_mut()
+        .zip(values.par_iter())
+        .for_each(|(out, value)| *out = B::from(*value));
+}
+
+// temporary buffers of ecfft evaluation reused across calls
+#[derive(Clone, Debug, Default)]
+pub struct Scratch {
+    buffer: Vec<Fp>,
+}
+
+impl Scratch {
+    // scratch for polynomials up to 2^k degree
+    pub fn new(k: usize) -> Self {
+        Scratch {
+            buffer: vec![Fp::zero(); 3 << k],
+        }
+    }
+
+    // values in the internal field and buffer of enter for 2^k degree
+    // growing only if it is smaller
+    fn get_mut(&mut self, k: usize) -> (&mut [Fp], &mut [Fp]) {
+        let size = 3 << k;
+        if self.buffer.len() < size {
+            self.buffer.resize(size, Fp::zero());
+        }
+        self.buffer[..size].split_at_mut(1 << k)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::EcFft;
+    use super::{EcFft, Field, Fq, Scratch, DEFAULT_PARALLEL_CUTOFF};
+    use crate::test::arb_poly_fq;
+    use rand_core::OsRng;
+    use rayon::prelude::*;
+    use rayon::ThreadPoolBuilder;
 
     #[test]
     fn test_precomputed_params() {
         let k = 14;
+        let report = EcFft::new().verify();
+
+        assert_eq!(report.checked, (1..=k).collect::<Vec<_>>());
+        assert!(report.is_valid(), "{:?}", report.violations);
+    }
+
+    #[test]
+    fn test_lazy_params() {
         let ecfft = EcFft::new();
+        let lazy_ecfft = EcFft::new_lazy();
 
-        for i in 1..k {
-            let mut n = 1 << (k - i);
-            let cache = ecfft.get_cache(k - i);
-            let coset = cache.get_coset();
+        assert!(lazy_ecfft.memory_footprint().is_empty());
+
+        let k = 6;
+        let poly = arb_poly_fq(k);
+        assert_eq!(
+            ecfft.evaluate(k, poly.clone()),
+            lazy_ecfft.evaluate(k, poly)
+        );
 
-            assert_eq!(coset.len(), n);
+        let footprint = lazy_ecfft.memory_footprint();
+        assert_eq!(
+            footprint.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+            (1..=k).collect::<Vec<_>>()
+        );
+        footprint
+            .iter()
+            .for_each(|(k, size)| assert_eq!(ecfft.get_cache(*k).memory_size(), *size));
 
-            for j in 0..(k - i) {
-                let tree = cache.get_tree(j);
-                let (s, s_prime) = tree.get_domain();
-                let factor = tree.get_factor();
-                let inv_factor = tree.get_inv_factor();
+        // concurrent initialisation builds each level once
+        (1..10usize).into_par_iter().for_each(|k| {
+            assert_eq!(lazy_ecfft.get_cache(k), ecfft.get_cache(k));
+        });
+        assert_eq!(lazy_ecfft.memory_footprint().len(), 9);
+    }
 
-                n /= 2;
+    #[test]
+    fn test_evaluate_with_scratch() {
+        let ecfft = EcFft::new();
+        let mut scratch = Scratch::new(10);
+        let buffer = scratch.buffer.as_ptr();
 
-                assert_eq!(s.len(), n);
-                assert_eq!(s_prime.len(), n);
-                assert_eq!(factor.len(), n / 2);
-                assert_eq!(inv_factor.len(), n / 2);
+        // smaller degrees reuse the same buffer
+        for k in (1..=10).rev() {
+            let poly = arb_poly_fq(k);
+            let evals = ecfft.evaluate_with_scratch(k, poly.clone(), &mut scratch);
+            assert_eq!(evals, poly.to_point_value(&ecfft.get_cache(k).coset));
+        }
+        assert_eq!(scratch.buffer.as_ptr(), buffer);
+
+        // grow on larger degree
+        let mut scratch = Scratch::default();
+        let k = 12;
+        let poly = arb_poly_fq(k);
+        assert_eq!(
+            ecfft.evaluate_with_scratch(k, poly.clone(), &mut scratch),
+            ecfft.evaluate(k, poly)
+        );
+        assert_eq!(scratch.buffer.len(), 3 << k);
+    }
+
+    #[test]
+    fn test_parallel_cutoff() {
+        let k = 12;
+        let poly = arb_poly_fq(k);
+        let mut ecfft = EcFft::new();
+        let expected = poly.to_point_value(&ecfft.get_cache(k).coset);
+
+        // fully serial, split down to the bottom and default
+        for cutoff in [k, 0, 5] {
+            ecfft.set_parallel_cutoff(cutoff);
+            assert_eq!(ecfft.evaluate(k, poly.clone()), expected);
+        }
+
+        let pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+        assert_eq!(ecfft.evaluate_in(&pool, k, poly), expected);
+    }
+
+    #[test]
+    fn test_evaluate_layered() {
+        let ecfft = EcFft::new();
+
+        for k in 1..14 {
+            let poly = arb_poly_fq(k);
+            assert_eq!(
+                ecfft.evaluate_layered(k, poly.clone()),
+                ecfft.evaluate(k, poly)
+            );
+        }
+    }
+
+    #[test]
+    fn test_evaluate_many_interleaved() {
+        let mut ecfft = EcFft::new();
+
+        for (k, m) in [(1, 1), (2, 3), (5, 4), (8, 1), (10, 5), (12, 8)] {
+            let polys = (0..m).map(|_| arb_poly_fq(k)).collect::<Vec<_>>();
+            let mut values = (0..m << k)
+                .map(|i| polys[i % m].values[i / m])
+                .collect::<Vec<_>>();
+            let evals = polys
+                .iter()
+                .map(|poly| ecfft.evaluate(k, poly.clone()).values)
+                .collect::<Vec<_>>();
+            let expected = (0..m << k).map(|i| evals[i % m][i / m]).collect::<Vec<_>>();
+
+            // fully serial, split down to the bottom, default and beyond the bit width
+            for cutoff in [20, 0, DEFAULT_PARALLEL_CUTOFF, 64, usize::MAX] {
+                let mut evals = values.clone();
+                ecfft.set_parallel_cutoff(cutoff);
+                ecfft.evaluate_many_interleaved(k, m, &mut evals);
+                assert_eq!(evals, expected);
+            }
+
+            EcFft::new_compact().evaluate_many_interleaved(k, m, &mut values);
+            assert_eq!(values, expected);
+        }
+    }
+
+    #[test]
+    fn test_evaluate_pruned() {
+        let mut ecfft = EcFft::new();
+
+        for k in 1..13 {
+            let poly = arb_poly_fq(k);
+            let n = 1 << k;
+            // leading coefficients, zero blocks, single coefficient and zero polynomial
+            let masks: [Box<dyn Fn(usize) -> bool>; 5] = [
+                Box::new(|i| i < 3),
+                Box::new(|i| i < n / 2 + 1),
+                Box::new(|i| (i / 4) % 3 == 1),
+                Box::new(|i| i == n - 1),
+                Box::new(|_| false),
+            ];
+            for mask in masks.iter() {
+                let mut sparse = poly.clone();
+                sparse
+                    .values
+                    .iter_mut()
+                    .enumerate()
+                    .filter(|(i, _)| !mask(*i))
+                    .for_each(|(_, coeff)| *coeff = Fq::zero());
+                let expected = ecfft.evaluate(k, sparse.clone());
+
+                // serial, split down to the bottom and default
+                for cutoff in [k, 0, DEFAULT_PARALLEL_CUTOFF] {
+                    ecfft.set_parallel_cutoff(cutoff);
+                    assert_eq!(ecfft.evaluate_pruned(k, sparse.clone()), expected);
+                }
+            }
+            assert_eq!(
+                ecfft.evaluate_pruned(k, poly.clone()),
+                ecfft.evaluate(k, poly)
+            );
+        }
+    }
+
+    #[test]
+    fn test_evaluate_partial() {
+        let ecfft = EcFft::new();
+
+        for k in 1..13 {
+            let poly = arb_poly_fq(k);
+            let n = 1 << k;
+            let evals = ecfft.evaluate(k, poly.clone()).get_values();
+            // single points, even points, few and many points with duplicates
+            let index_sets = [
+                vec![0],
+                vec![n - 1],
+                (0..n).step_by(4).take(40).collect::<Vec<_>>(),
+                vec![1, n / 2, 0, 1],
+                (0..n).map(|i| (i * 7 + 3) % n).collect(),
+            ];
+            for indices in index_sets.iter() {
+                let expected = indices.iter().map(|i| evals[*i]).collect::<Vec<_>>();
+                assert_eq!(ecfft.evaluate_partial(k, poly.clone(), indices), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_update_evaluations() {
+        let mut ecfft = EcFft::new();
+
+        for k in [1, 4, 11, 12] {
+            let poly = arb_poly_fq(k);
+            let n = 1 << k;
+            // duplicated and unsorted indices including both ends
+            let changes = [
+                (n - 1, Fq::random(OsRng)),
+                (0, Fq::random(OsRng)),
+                (n / 2, Fq::random(OsRng)),
+                (n - 1, Fq::random(OsRng)),
+            ];
+
+            // serial and parallel
+            for cutoff in [k, 0] {
+                ecfft.set_parallel_cutoff(cutoff);
+                let mut evals = ecfft.evaluate(k, poly.clone());
+                ecfft.update_evaluations(k, &mut evals, &changes);
+                ecfft.update_evaluations(k, &mut evals, &[]);
+
+                let mut updated = poly.clone();
+                changes
+                    .iter()
+                    .for_each(|(index, delta)| updated.values[*index] += delta);
+                assert_eq!(evals, ecfft.evaluate(k, updated));
+            }
+        }
+    }
+
+    #[test]
+    fn test_evaluate_at() {
+        let ecfft = EcFft::new();
+        let footprint = ecfft.memory_footprint();
+
+        for k in [1, 2, 7, 10, 14] {
+            let poly = arb_poly_fq(k);
+            let evals = ecfft.evaluate(k, poly.clone());
+            let z = Fq::random(OsRng);
+            assert_eq!(
+                ecfft.evaluate_at(k, &evals, z),
+                poly.clone().polynomial_evaluation(z)
+            );
+
+            // points of the coset
+            let coset = ecfft.get_cache(k).coset.clone();
+            for i in [0, (1 << k) - 1] {
+                assert_eq!(ecfft.evaluate_at(k, &evals, coset[i]), evals.values[i]);
             }
         }
+
+        // weights are reported apart from the params
+        assert_eq!(ecfft.memory_footprint(), footprint);
+        assert!(ecfft.derived_footprint().iter().any(|(k, _)| *k == 14));
+    }
+
+    #[test]
+    fn test_compact_params() {
+        let ecfft = EcFft::new();
+        let compact = EcFft::new_compact();
+
+        for k in 1..14 {
+            let poly = arb_poly_fq(k);
+            assert_eq!(
+                compact.evaluate(k, poly.clone()),
+                ecfft.evaluate(k, poly.clone())
+            );
+            assert_eq!(
+                compact.evaluate_layered(k, poly.clone()),
+                ecfft.evaluate(k, poly)
+            );
+        }
+
+        let size = |ecfft: &EcFft| {
+            ecfft
+                .memory_footprint()
+                .iter()
+                .map(|(_, size)| size)
+                .sum::<usize>()
+        };
+        assert!(size(&compact) < size(&ecfft));
+
+        let report = compact.verify();
+        assert!(report.is_valid(), "{:?}", report.violations);
+    }
+
+    #[test]
+    fn test_shared_params() {
+        let ecfft = EcFft::new_lazy();
+        let handle = ecfft.clone();
+
+        let k = 5;
+        let poly = arb_poly_fq(k);
+        let evals = handle.evaluate(k, poly.clone());
+
+        // params initialised through a clone are visible from the original
+        assert!(std::ptr::eq(&ecfft.caches[..], &handle.caches[..]));
+        assert_eq!(ecfft.memory_footprint().len(), k);
+        assert_eq!(ecfft.evaluate(k, poly.clone()), evals);
+
+        assert!(std::ptr::eq(EcFft::global(), EcFft::global()));
+        assert_eq!(EcFft::global().evaluate(k, poly), evals);
+    }
+
+    #[cfg(feature = "precomputed")]
+    #[test]
+    fn test_embedded_params() {
+        let max_k = 14;
+        let embedded = EcFft::new();
+        let computed = EcFft::from_caches(max_k, super::utils::precompute_caches(max_k));
+        assert_eq!(embedded.memory_footprint().len(), max_k);
+
+        for k in 1..=max_k {
+            assert_eq!(embedded.get_cache(k), computed.get_cache(k));
+        }
     }
 }
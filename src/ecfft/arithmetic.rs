@@ -1,47 +1,56 @@
+use super::fftree::{Extension, FactorView};
+#[cfg(feature = "simd")]
+use super::field::Simd;
+use super::field::{sum_of_products, Fp};
 use super::EcFftCache;
 
-use pairing::bn256::Fq as Fp;
+use pairing::group::ff::Field;
 use rayon::{join, prelude::*};
 
+// elements of each simd call in parallel kernels
+#[cfg(feature = "simd")]
+const SIMD_CHUNK: usize = 64;
+
 // low degree extention using divide and conquer algorithm
 pub(crate) fn serial_low_degree_extention(
     coeffs: &mut [Fp],
     coeffs_prime: &mut [Fp],
     mut n: usize,
     k: usize,
-    _: usize,
+    depth: usize,
     caches: &EcFftCache,
 ) {
-    for depth in 0..(k - 2) {
-        let inv_factor = &caches.trees[depth].get_inv_factor();
+    for i in 0..(k - 2) {
+        let inv_factor = caches.trees[depth + i].get_inv_factor();
         poly_conversion(coeffs, coeffs_prime, n, n >> 1, inv_factor);
         n >>= 1;
     }
+    let last_tree = caches.get_last_tree();
     coeffs
         .chunks_mut(2)
         .zip(coeffs_prime.chunks_mut(2))
-        .for_each(|(coeffs, coeffs_prime)| {
-            bottom_poly_conversion(coeffs, coeffs_prime, caches.get_last_tree())
-        });
+        .for_each(|(coeffs, coeffs_prime)| bottom_poly_conversion(coeffs, coeffs_prime, last_tree));
     n = 4;
-    for depth in 0..(k - 2) {
-        let factor = &caches.trees[k - depth - 3].get_factor();
+    for i in 0..(k - 2) {
+        let factor = caches.trees[depth + k - i - 3].get_factor();
         poly_conversion(coeffs, coeffs_prime, n, n >> 1, factor);
         n <<= 1;
     }
 }
 
 // low degree extention using divide and conquer algorithm
+// subtrees of 2^cutoff or smaller size are converted serially
 pub(crate) fn parallel_low_degree_extention(
     coeffs: &mut [Fp],
     coeffs_prime: &mut [Fp],
     n: usize,
     k: usize,
     depth: usize,
+    cutoff: usize,
     caches: &EcFftCache,
 ) {
-    if k == 2 {
-        bottom_poly_conversion(coeffs, coeffs_prime, caches.get_last_tree());
+    if k == 2 || k <= cutoff {
+        serial_low_degree_extention(coeffs, coeffs_prime, n, k, depth, caches);
         return;
     }
 
@@ -51,53 +60,144 @@ pub(crate) fn parallel_low_degree_extention(
     let (left_prime, right_prime) = coeffs_prime.split_at_mut(half_n);
     let (factor, inv_factor) = cache.get_factors();
 
-    serial_matrix_arithmetic(left, right, left_prime, right_prime, inv_factor);
+    parallel_matrix_arithmetic(left, right, left_prime, right_prime, inv_factor);
     join(
-        || parallel_low_degree_extention(left, left_prime, half_n, k - 1, depth + 1, caches),
-        || parallel_low_degree_extention(right, right_prime, half_n, k - 1, depth + 1, caches),
+        || {
+            parallel_low_degree_extention(
+                left,
+                left_prime,
+                half_n,
+                k - 1,
+                depth + 1,
+                cutoff,
+                caches,
+            )
+        },
+        || {
+            parallel_low_degree_extention(
+                right,
+                right_prime,
+                half_n,
+                k - 1,
+                depth + 1,
+                cutoff,
+                caches,
+            )
+        },
     );
-    serial_matrix_arithmetic(left, right, left_prime, right_prime, factor);
+    parallel_matrix_arithmetic(left, right, left_prime, right_prime, factor);
 }
 
 // matrix arithmetic with factor
-pub fn serial_matrix_arithmetic(
+pub(crate) fn serial_matrix_arithmetic(
     left: &mut [Fp],
     right: &mut [Fp],
     left_prime: &mut [Fp],
     right_prime: &mut [Fp],
-    factor: &Vec<((Fp, Fp), (Fp, Fp))>,
+    factor: FactorView,
 ) {
-    left.iter_mut()
-        .zip(right.iter_mut())
-        .zip(left_prime.iter_mut())
-        .zip(right_prime.iter_mut())
-        .zip(factor.iter())
-        .for_each(|((((a, b), c), d), e)| matrix_arithmetic(a, b, c, d, e))
+    apply_factor(left, right, factor);
+    apply_factor(left_prime, right_prime, factor);
 }
 
-fn matrix_arithmetic(
-    a: &mut Fp,
-    b: &mut Fp,
-    c: &mut Fp,
-    d: &mut Fp,
-    factor: &((Fp, Fp), (Fp, Fp)),
+// matrix arithmetic with factor over parallel iterator
+pub(crate) fn parallel_matrix_arithmetic(
+    left: &mut [Fp],
+    right: &mut [Fp],
+    left_prime: &mut [Fp],
+    right_prime: &mut [Fp],
+    factor: FactorView,
 ) {
-    let ((f0, f1), (f2, f3)) = factor;
-    let tmp = f2 * *a + f3 * *b;
-    *a = f0 * *a + f1 * *b;
-    *b = tmp;
-    let tmp = f2 * *c + f3 * *d;
-    *c = f0 * *c + f1 * *d;
-    *d = tmp;
+    par_apply_factor(left, right, factor);
+    par_apply_factor(left_prime, right_prime, factor);
+}
+
+// multiply each (left, right) pair by the matrix of the same index
+// the layout is matched once so that every entry is read from contiguous arrays
+fn apply_factor(left: &mut [Fp], right: &mut [Fp], factor: FactorView) {
+    #[cfg(feature = "simd")]
+    if let (FactorView::Full(entries), Some(simd)) = (factor, Simd::detect()) {
+        return simd.matrix_arithmetic(left, right, entries);
+    }
+
+    let pairs = left.iter_mut().zip(right.iter_mut());
+    match factor {
+        FactorView::Full([f0, f1, f2, f3]) => pairs
+            .zip(f0.iter().zip(f1.iter()))
+            .zip(f2.iter().zip(f3.iter()))
+            .for_each(|(((l, r), (f0, f1)), (f2, f3))| {
+                let tmp = sum_of_products(*f2, *l, *f3, *r);
+                *l = sum_of_products(*f0, *l, *f1, *r);
+                *r = tmp;
+            }),
+        FactorView::Compact { p, q, a, b } => pairs
+            .zip(p.iter().zip(q.iter()))
+            .zip(a.iter().zip(b.iter()))
+            .for_each(|(((l, r), (p, q)), (a, b))| {
+                let tmp = q * (*l + b * *r);
+                *l = p * (*l + a * *r);
+                *r = tmp;
+            }),
+        FactorView::CompactInverse { u, w, a, b } => pairs
+            .zip(u.iter().zip(w.iter()))
+            .zip(a.iter().zip(b.iter()))
+            .for_each(|(((l, r), (u, w)), (a, b))| {
+                let (x, y) = (u * *l, w * *r);
+                *l = sum_of_products(*b, x, -*a, y);
+                *r = y - x;
+            }),
+    }
+}
+
+fn par_apply_factor(left: &mut [Fp], right: &mut [Fp], factor: FactorView) {
+    #[cfg(feature = "simd")]
+    if let (FactorView::Full([f0, f1, f2, f3]), Some(simd)) = (factor, Simd::detect()) {
+        return left
+            .par_chunks_mut(SIMD_CHUNK)
+            .zip(right.par_chunks_mut(SIMD_CHUNK))
+            .zip(f0.par_chunks(SIMD_CHUNK).zip(f1.par_chunks(SIMD_CHUNK)))
+            .zip(f2.par_chunks(SIMD_CHUNK).zip(f3.par_chunks(SIMD_CHUNK)))
+            .for_each(|(((l, r), (f0, f1)), (f2, f3))| {
+                simd.matrix_arithmetic(l, r, [f0, f1, f2, f3])
+            });
+    }
+
+    let pairs = left.par_iter_mut().zip(right.par_iter_mut());
+    match factor {
+        FactorView::Full([f0, f1, f2, f3]) => pairs
+            .zip(f0.par_iter().zip(f1.par_iter()))
+            .zip(f2.par_iter().zip(f3.par_iter()))
+            .for_each(|(((l, r), (f0, f1)), (f2, f3))| {
+                let tmp = sum_of_products(*f2, *l, *f3, *r);
+                *l = sum_of_products(*f0, *l, *f1, *r);
+                *r = tmp;
+            }),
+        FactorView::Compact { p, q, a, b } => pairs
+            .zip(p.par_iter().zip(q.par_iter()))
+            .zip(a.par_iter().zip(b.par_iter()))
+            .for_each(|(((l, r), (p, q)), (a, b))| {
+                let tmp = q * (*l + b * *r);
+                *l = p * (*l + a * *r);
+                *r = tmp;
+            }),
+        FactorView::CompactInverse { u, w, a, b } => pairs
+            .zip(u.par_iter().zip(w.par_iter()))
+            .zip(a.par_iter().zip(b.par_iter()))
+            .for_each(|(((l, r), (u, w)), (a, b))| {
+                let (x, y) = (u * *l, w * *r);
+                *l = sum_of_products(*b, x, -*a, y);
+                *r = y - x;
+            }),
+    }
 }
 
 pub(crate) fn serial_integrate_evaluation(
     coeffs: &mut [Fp],
-    low_prime: Vec<Fp>,
-    high_prime: Vec<Fp>,
-    low: Vec<Fp>,
-    high: Vec<Fp>,
-    powered_coset: &Vec<Fp>,
+    low_prime: &[Fp],
+    high_prime: &[Fp],
+    low: &[Fp],
+    high: &[Fp],
+    powered_coset: &[Fp],
 ) {
     coeffs
         .chunks_mut(2)
@@ -114,11 +214,11 @@ pub(crate) fn serial_integrate_evaluation(
 
 pub(crate) fn parallel_integrate_evaluation(
     coeffs: &mut [Fp],
-    low_prime: Vec<Fp>,
-    high_prime: Vec<Fp>,
-    low: Vec<Fp>,
-    high: Vec<Fp>,
-    powered_coset: &Vec<Fp>,
+    low_prime: &[Fp],
+    high_prime: &[Fp],
+    low: &[Fp],
+    high: &[Fp],
+    powered_coset: &[Fp],
 ) {
     coeffs
         .par_chunks_mut(2)
@@ -138,7 +238,7 @@ pub(crate) fn poly_conversion(
     coeffs_prime: &mut [Fp],
     n: usize,
     half_n: usize,
-    factor: &Vec<((Fp, Fp), (Fp, Fp))>,
+    factor: FactorView,
 ) {
     coeffs
         .chunks_mut(n)
@@ -153,14 +253,366 @@ pub(crate) fn poly_conversion(
 pub(crate) fn bottom_poly_conversion(
     coeffs: &mut [Fp],
     coeffs_prime: &mut [Fp],
-    (((f4, f5), (f6, f7)), ((f0, f1), (f2, f3))): (&((Fp, Fp), (Fp, Fp)), &((Fp, Fp), (Fp, Fp))),
-) {
-    let tmp_a = f2 * coeffs[0] + f3 * coeffs[1];
-    let tmp_b = f0 * coeffs[0] + f1 * coeffs[1];
-    let tmp_prime_a = f2 * coeffs_prime[0] + f3 * coeffs_prime[1];
-    let tmp_prime_b = f0 * coeffs_prime[0] + f1 * coeffs_prime[1];
-    coeffs[0] = f4 * tmp_b + f5 * tmp_a;
-    coeffs[1] = f6 * tmp_b + f7 * tmp_a;
-    coeffs_prime[0] = f4 * tmp_prime_b + f5 * tmp_prime_a;
-    coeffs_prime[1] = f6 * tmp_prime_b + f7 * tmp_prime_a;
+    (((f4, f5), (f6, f7)), ((f0, f1), (f2, f3))): (((Fp, Fp), (Fp, Fp)), ((Fp, Fp), (Fp, Fp))),
+) {
+    let tmp_a = sum_of_products(f2, coeffs[0], f3, coeffs[1]);
+    let tmp_b = sum_of_products(f0, coeffs[0], f1, coeffs[1]);
+    let tmp_prime_a = sum_of_products(f2, coeffs_prime[0], f3, coeffs_prime[1]);
+    let tmp_prime_b = sum_of_products(f0, coeffs_prime[0], f1, coeffs_prime[1]);
+    coeffs[0] = sum_of_products(f4, tmp_b, f5, tmp_a);
+    coeffs[1] = sum_of_products(f6, tmp_b, f7, tmp_a);
+    coeffs_prime[0] = sum_of_products(f4, tmp_prime_b, f5, tmp_prime_a);
+    coeffs_prime[1] = sum_of_products(f6, tmp_prime_b, f7, tmp_prime_a);
+}
+
+// apply factor to the halves of every 2 * factor size chunk of values at once
+pub(crate) fn layer_conversion(values: &mut [Fp], factor: FactorView) {
+    values.par_chunks_mut(factor.len() << 1).for_each(|chunk| {
+        let (left, right) = chunk.split_at_mut(factor.len());
+        apply_factor(left, right, factor)
+    });
+}
+
+// bottom conversion of every 2 size chunk of values at once
+pub(crate) fn bottom_layer_conversion(
+    values: &mut [Fp],
+    (((f4, f5), (f6, f7)), ((f0, f1), (f2, f3))): (((Fp, Fp), (Fp, Fp)), ((Fp, Fp), (Fp, Fp))),
+) {
+    values.par_chunks_mut(2).for_each(|values| {
+        let tmp_a = sum_of_products(f2, values[0], f3, values[1]);
+        let tmp_b = sum_of_products(f0, values[0], f1, values[1]);
+        values[0] = sum_of_products(f4, tmp_b, f5, tmp_a);
+        values[1] = sum_of_products(f6, tmp_b, f7, tmp_a);
+    });
+}
+
+// integrate low and high evaluations of every 2^k size node at once
+pub(crate) fn layer_integrate_evaluation(
+    out: &mut [Fp],
+    snapshot: &[Fp],
+    values: &[Fp],
+    powered_coset: &[Fp],
+) {
+    let half_n = powered_coset.len() >> 1;
+    out.par_chunks_mut(powered_coset.len())
+        .zip(snapshot.par_chunks(powered_coset.len()))
+        .zip(values.par_chunks(powered_coset.len()))
+        .for_each(|((out, snapshot), values)| {
+            let (low_prime, high_prime) = snapshot.split_at(half_n);
+            let (low, high) = values.split_at(half_n);
+            serial_integrate_evaluation(out, low_prime, high_prime, low, high, powered_coset)
+        });
+}
+
+// low degree extention of one polynomial converting every node of the same depth at once
+pub(crate) fn single_low_degree_extention<E: Extension>(
+    coeffs: &mut [Fp],
+    k: usize,
+    depth: usize,
+    caches: &E,
+) {
+    let conversion = |coeffs: &mut [Fp], factor: FactorView| {
+        coeffs.chunks_mut(factor.len() << 1).for_each(|chunk| {
+            let (left, right) = chunk.split_at_mut(factor.len());
+            apply_factor(left, right, factor)
+        })
+    };
+    for i in 0..(k - 2) {
+        conversion(coeffs, caches.get_factors(depth + i).1);
+    }
+    let last_tree = caches.get_last_tree();
+    coeffs
+        .chunks_mut(2)
+        .for_each(|coeffs| interleaved_bottom_poly_conversion(coeffs, 1, last_tree));
+    for i in (0..(k - 2)).rev() {
+        conversion(coeffs, caches.get_factors(depth + i).0);
+    }
+}
+
+// low degree extention of one polynomial
+// nodes of 2^cutoff or smaller degree are converted serially
+pub(crate) fn parallel_single_low_degree_extention<E: Extension>(
+    coeffs: &mut [Fp],
+    k: usize,
+    depth: usize,
+    cutoff: usize,
+    caches: &E,
+) {
+    if k == 2 || k <= cutoff {
+        single_low_degree_extention(coeffs, k, depth, caches);
+        return;
+    }
+
+    let (factor, inv_factor) = caches.get_factors(depth);
+    let (left, right) = coeffs.split_at_mut(coeffs.len() >> 1);
+
+    par_apply_factor(left, right, inv_factor);
+    join(
+        || parallel_single_low_degree_extention(left, k - 1, depth + 1, cutoff, caches),
+        || parallel_single_low_degree_extention(right, k - 1, depth + 1, cutoff, caches),
+    );
+    par_apply_factor(left, right, factor);
+}
+
+// low degree extention of m polynomials interleaved so that the i-th coefficients are contiguous
+// n is the size of each polynomial
+pub(crate) fn serial_interleaved_low_degree_extention(
+    coeffs: &mut [Fp],
+    coeffs_prime: &mut [Fp],
+    mut n: usize,
+    k: usize,
+    depth: usize,
+    m: usize,
+    caches: &EcFftCache,
+) {
+    for i in 0..(k - 2) {
+        let inv_factor = caches.trees[depth + i].get_inv_factor();
+        interleaved_poly_conversion(coeffs, coeffs_prime, n, m, inv_factor);
+        n >>= 1;
+    }
+    let last_tree = caches.get_last_tree();
+    coeffs
+        .chunks_mut(2 * m)
+        .zip(coeffs_prime.chunks_mut(2 * m))
+        .for_each(|(coeffs, coeffs_prime)| {
+            interleaved_bottom_poly_conversion(coeffs, m, last_tree);
+            interleaved_bottom_poly_conversion(coeffs_prime, m, last_tree);
+        });
+    n = 4;
+    for i in 0..(k - 2) {
+        let factor = caches.trees[depth + k - i - 3].get_factor();
+        interleaved_poly_conversion(coeffs, coeffs_prime, n, m, factor);
+        n <<= 1;
+    }
+}
+
+// low degree extention of m interleaved polynomials
+// subtrees of 2^cutoff or smaller size in total are converted serially
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn parallel_interleaved_low_degree_extention(
+    coeffs: &mut [Fp],
+    coeffs_prime: &mut [Fp],
+    n: usize,
+    k: usize,
+    depth: usize,
+    m: usize,
+    cutoff: usize,
+    caches: &EcFftCache,
+) {
+    if k == 2 || !exceeds_cutoff(coeffs.len(), cutoff) {
+        serial_interleaved_low_degree_extention(coeffs, coeffs_prime, n, k, depth, m, caches);
+        return;
+    }
+
+    let half_n = n >> 1;
+    let cache = caches.get_tree(depth);
+    let (left, right) = coeffs.split_at_mut(half_n * m);
+    let (left_prime, right_prime) = coeffs_prime.split_at_mut(half_n * m);
+    let (factor, inv_factor) = cache.get_factors();
+
+    par_apply_interleaved_factor(left, right, m, inv_factor);
+    par_apply_interleaved_factor(left_prime, right_prime, m, inv_factor);
+    join(
+        || {
+            parallel_interleaved_low_degree_extention(
+                left,
+                left_prime,
+                half_n,
+                k - 1,
+                depth + 1,
+                m,
+                cutoff,
+                caches,
+            )
+        },
+        || {
+            parallel_interleaved_low_degree_extention(
+                right,
+                right_prime,
+                half_n,
+                k - 1,
+                depth + 1,
+                m,
+                cutoff,
+                caches,
+            )
+        },
+    );
+    par_apply_interleaved_factor(left, right, m, factor);
+    par_apply_interleaved_factor(left_prime, right_prime, m, factor);
+}
+
+// matrix arithmetic on every n size node of m interleaved polynomials
+pub(crate) fn interleaved_poly_conversion(
+    coeffs: &mut [Fp],
+    coeffs_prime: &mut [Fp],
+    n: usize,
+    m: usize,
+    factor: FactorView,
+) {
+    coeffs
+        .chunks_mut(n * m)
+        .zip(coeffs_prime.chunks_mut(n * m))
+        .for_each(|(coeffs, coeffs_prime)| {
+            let (left, right) = coeffs.split_at_mut(n * m / 2);
+            let (left_prime, right_prime) = coeffs_prime.split_at_mut(n * m / 2);
+            apply_interleaved_factor(left, right, m, factor);
+            apply_interleaved_factor(left_prime, right_prime, m, factor);
+        });
+}
+
+pub(crate) fn interleaved_bottom_poly_conversion(
+    coeffs: &mut [Fp],
+    m: usize,
+    (((f4, f5), (f6, f7)), ((f0, f1), (f2, f3))): (((Fp, Fp), (Fp, Fp)), ((Fp, Fp), (Fp, Fp))),
+) {
+    let (left, right) = coeffs.split_at_mut(m);
+    left.iter_mut().zip(right.iter_mut()).for_each(|(l, r)| {
+        let tmp_a = sum_of_products(f2, *l, f3, *r);
+        let tmp_b = sum_of_products(f0, *l, f1, *r);
+        *l = sum_of_products(f4, tmp_b, f5, tmp_a);
+        *r = sum_of_products(f6, tmp_b, f7, tmp_a);
+    });
+}
+
+// load each matrix once and multiply the m (left, right) pairs of the same index
+fn apply_interleaved_factor(left: &mut [Fp], right: &mut [Fp], m: usize, factor: FactorView) {
+    left.chunks_mut(m)
+        .zip(right.chunks_mut(m))
+        .enumerate()
+        .for_each(|(i, (left, right))| interleaved_matrix_arithmetic(left, right, factor.entry(i)));
+}
+
+fn par_apply_interleaved_factor(left: &mut [Fp], right: &mut [Fp], m: usize, factor: FactorView) {
+    left.par_chunks_mut(m)
+        .zip(right.par_chunks_mut(m))
+        .enumerate()
+        .for_each(|(i, (left, right))| interleaved_matrix_arithmetic(left, right, factor.entry(i)));
+}
+
+fn interleaved_matrix_arithmetic(
+    left: &mut [Fp],
+    right: &mut [Fp],
+    ((f0, f1), (f2, f3)): ((Fp, Fp), (Fp, Fp)),
+) {
+    #[cfg(feature = "simd")]
+    if let Some(simd) = Simd::detect() {
+        return simd.uniform_matrix_arithmetic(left, right, [f0, f1, f2, f3]);
+    }
+
+    left.iter_mut().zip(right.iter_mut()).for_each(|(l, r)| {
+        let tmp = sum_of_products(f2, *l, f3, *r);
+        *l = sum_of_products(f0, *l, f1, *r);
+        *r = tmp;
+    });
+}
+
+// integrate m interleaved polynomials loading each power of coset once
+pub(crate) fn serial_interleaved_integrate_evaluation(
+    coeffs: &mut [Fp],
+    low_prime: &[Fp],
+    high_prime: &[Fp],
+    low: &[Fp],
+    high: &[Fp],
+    powered_coset: &[Fp],
+    m: usize,
+) {
+    coeffs
+        .chunks_mut(2 * m)
+        .zip(low_prime.chunks(m))
+        .zip(high_prime.chunks(m))
+        .zip(low.chunks(m))
+        .zip(high.chunks(m))
+        .zip(powered_coset.chunks(2))
+        .for_each(|(((((coeffs, a), b), c), d), e)| {
+            interleaved_integrate(coeffs, a, b, c, d, (e[0], e[1]))
+        });
+}
+
+pub(crate) fn parallel_interleaved_integrate_evaluation(
+    coeffs: &mut [Fp],
+    low_prime: &[Fp],
+    high_prime: &[Fp],
+    low: &[Fp],
+    high: &[Fp],
+    powered_coset: &[Fp],
+    m: usize,
+) {
+    coeffs
+        .par_chunks_mut(2 * m)
+        .zip(low_prime.par_chunks(m))
+        .zip(high_prime.par_chunks(m))
+        .zip(low.par_chunks(m))
+        .zip(high.par_chunks(m))
+        .zip(powered_coset.par_chunks(2))
+        .for_each(|(((((coeffs, a), b), c), d), e)| {
+            interleaved_integrate(coeffs, a, b, c, d, (e[0], e[1]))
+        });
+}
+
+fn interleaved_integrate(
+    coeffs: &mut [Fp],
+    low_prime: &[Fp],
+    high_prime: &[Fp],
+    low: &[Fp],
+    high: &[Fp],
+    (even, odd): (Fp, Fp),
+) {
+    let (even_points, odd_points) = coeffs.split_at_mut(low.len());
+    even_points
+        .iter_mut()
+        .zip(low_prime.iter().zip(high_prime.iter()))
+        .for_each(|(point, (a, b))| *point = a + even * b);
+    odd_points
+        .iter_mut()
+        .zip(low.iter().zip(high.iter()))
+        .for_each(|(point, (c, d))| *point = c + odd * d);
+}
+
+// x^{2^log - 1} by repeated squaring
+pub(crate) fn pow_two_minus_one(x: Fp, log: u32) -> Fp {
+    (0..log).fold(Fp::one(), |acc, _| acc.square() * x)
+}
+
+// whether length is more than 2^cutoff, any length is within cutoff of the bit width or more
+pub(crate) fn exceeds_cutoff(len: usize, cutoff: usize) -> bool {
+    let size = u32::try_from(cutoff)
+        .ok()
+        .and_then(|cutoff| 1usize.checked_shl(cutoff));
+    matches!(size, Some(size) if len > size)
+}
+
+// x^exp by square and multiply over the significant bits of exp
+pub(crate) fn pow_small<F: Field>(x: F, exp: usize) -> F {
+    (0..usize::BITS - exp.leading_zeros())
+        .rev()
+        .fold(F::one(), |acc, i| {
+            let acc = acc.square();
+            if (exp >> i) & 1 == 1 {
+                acc * x
+            } else {
+                acc
+            }
+        })
+}
+
+// x^{2^times} by repeated squaring
+pub(crate) fn square_times(x: Fp, times: usize) -> Fp {
+    (0..times).fold(x, |acc, _| acc.square())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::pow_small;
+    use crate::ecfft::field::Fp;
+    use pairing::group::ff::Field;
+    use rand_core::OsRng;
+
+    #[test]
+    fn test_pow_small() {
+        let x = Fp::random(OsRng);
+        for exp in [0, 1, 2, 7, 1 << 13, (1 << 14) - 1] {
+            assert_eq!(pow_small(x, exp), x.pow_vartime(&[exp as u64]));
+        }
+    }
 }
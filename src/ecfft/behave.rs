@@ -90,6 +90,17 @@ macro_rules! curve_projective_arithmetic {
                 $curve_affine { x, y }
             }
 
+            // affine x coordinates of points sharing one inversion
+            pub(crate) fn batch_point_projective(points: &[$curve]) -> Vec<$field> {
+                let mut zinvs = points.iter().map(|point| point.z).collect::<Vec<_>>();
+                batch_inversion(&mut zinvs);
+                points
+                    .iter()
+                    .zip(zinvs.iter())
+                    .map(|(point, zinv)| point.x * zinv.square())
+                    .collect()
+            }
+
             fn double(&self) -> Self {
                 // https://www.hyperelliptic.org/EFD/g1p/auto-shortw-jacobian-0.html#doubling-dbl-2007-bl
 
@@ -14,11 +14,13 @@
 use core::fmt::{self, Debug};
 use core::ops::{Add, Mul};
 
+use crate::utils::batch_inversion;
+
 use super::behave::{
     curve_affine_coordinate_method, curve_constant_params, curve_projective_arithmetic,
     curve_projective_coordinate_method,
 };
-use pairing::bn256::Fq as Fp;
+use super::field::Fp;
 use pairing::group::ff::{Field, PrimeField};
 use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
 
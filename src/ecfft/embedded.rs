@@ -0,0 +1,13 @@
+// params generated by build script as static tables
+// every level is initialised from the start so no decoding nor allocation is needed
+
+use super::fftree::{FactorMatrix, FfTree};
+use super::field::Fp;
+use super::utils::EcFftCache;
+use crate::polynomial::SharedPoints;
+
+use once_cell::sync::OnceCell;
+use pairing::bn256::Fq;
+use std::borrow::Cow;
+
+include!(concat!(env!("OUT_DIR"), "/ecfft_params.rs"));
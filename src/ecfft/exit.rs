@@ -0,0 +1,292 @@
+use super::arithmetic::parallel_single_low_degree_extention;
+use super::field::Fp;
+use super::utils::{EcFftCache, ExitParams};
+use super::{convert, internal, EcFft, Scratch};
+use crate::polynomial::{short_multiply, Coefficients, Multiplier, PointValue, Polynomial};
+use crate::utils::parallel_batch_inversion;
+
+use pairing::bn256::Fq;
+use pairing::group::ff::Field;
+use rayon::{join, prelude::*};
+
+impl EcFft {
+    // coefficients of 2^k degree polynomial from its evaluations on the coset
+    pub fn interpolate(
+        &self,
+        k: usize,
+        evals: Polynomial<Fq, PointValue>,
+    ) -> Polynomial<Fq, Coefficients> {
+        assert!(k <= self.max_k);
+        assert!(
+            evals.domain() == &self.domain(k),
+            "point values are not on the ecfft coset"
+        );
+        let mut coeffs = evals.values;
+        let mut values = internal(&coeffs);
+        self.exit(&mut values, k);
+        convert(&values, &mut coeffs);
+        Polynomial::<Fq, Coefficients>::new(coeffs)
+    }
+
+    // inverse of enter from evaluations on the coset to coefficients
+    // p = low + x^{n/2} high where low is recovered on s by two reductions and high follows
+    // from it, then both are interpolated on s which is the coset of half degree
+    pub(super) fn exit(&self, values: &mut [Fp], k: usize) {
+        let cache = self.get_cache(k);
+        if k == 1 {
+            // line through (x_0, v_0) and (x_1, v_1)
+            let (x0, x1) = (cache.powered_coset[0], cache.powered_coset[1]);
+            let slope = (values[0] - values[1]) * (x0 - x1).invert().unwrap();
+            values[0] -= slope * x0;
+            values[1] = slope;
+            return;
+        }
+
+        let params = self.get_exit_params(k);
+        let (even, odd): (Vec<_>, Vec<_>) = values.chunks(2).map(|pair| (pair[0], pair[1])).unzip();
+
+        // p / z mod x^{n/2} times z^2 reduces to p z / z = p mod x^{n/2}
+        let (mut reduced, mut reduced_prime) = self.reduce(cache, params, &even, &odd, k);
+        let (squared, squared_prime) = &params.squared;
+        mul_assign(&mut reduced, squared);
+        mul_assign(&mut reduced_prime, squared_prime);
+        let (mut low, _) = self.reduce(cache, params, &reduced, &reduced_prime, k);
+
+        let mut high = even
+            .par_iter()
+            .zip(low.par_iter())
+            .zip(params.inv_powered.par_iter())
+            .map(|((p, low), inv)| (*p - low) * inv)
+            .collect::<Vec<_>>();
+
+        if k > self.parallel_cutoff {
+            join(
+                || self.exit(&mut low, k - 1),
+                || self.exit(&mut high, k - 1),
+            );
+        } else {
+            self.exit(&mut low, k - 1);
+            self.exit(&mut high, k - 1);
+        }
+
+        let (low_half, high_half) = values.split_at_mut(low.len());
+        low_half.copy_from_slice(&low);
+        high_half.copy_from_slice(&high);
+    }
+
+    // montgomery reduction p / z mod x^{n/2} of p of 2^k degree from its values on s and s_prime
+    // t = -p / x^{n/2} on s makes p + t x^{n/2} divisible by z whose quotient is the reduction
+    fn reduce(
+        &self,
+        cache: &EcFftCache,
+        params: &ExitParams,
+        values: &[Fp],
+        values_prime: &[Fp],
+        k: usize,
+    ) -> (Vec<Fp>, Vec<Fp>) {
+        let mut t = values
+            .par_iter()
+            .zip(params.inv_powered.par_iter())
+            .map(|(p, inv)| -(*p * inv))
+            .collect::<Vec<_>>();
+        parallel_single_low_degree_extention(&mut t, k, 0, self.parallel_cutoff, cache);
+
+        let reduced_prime = values_prime
+            .par_iter()
+            .zip(t.par_iter())
+            .zip(cache.powered_coset.par_chunks(2))
+            .zip(params.inv_vanishing.par_iter())
+            .map(|(((p, t), powered), inv)| (*p + *t * powered[1]) * inv)
+            .collect::<Vec<_>>();
+        let mut reduced = reduced_prime.clone();
+        parallel_single_low_degree_extention(
+            &mut reduced,
+            k,
+            0,
+            self.parallel_cutoff,
+            &cache.reverse(&params.reverse),
+        );
+
+        (reduced, reduced_prime)
+    }
+
+    // get params of exit from 2^k evaluations initialising them if they are not yet
+    // they need exit of half degree whose params are initialised in turn
+    fn get_exit_params(&self, k: usize) -> &ExitParams {
+        let cache = self.get_cache(k);
+        self.get_derived(k).exit_params.get_or_init(|| {
+            let half = 1 << (k - 1);
+            let (powered, powered_prime): (Vec<_>, Vec<_>) = cache
+                .powered_coset
+                .chunks(2)
+                .map(|pair| (pair[0], pair[1]))
+                .unzip();
+            let mut inv_powered = powered.clone();
+            parallel_batch_inversion(&mut inv_powered);
+
+            // z = x^{n/2} + r on s_prime where r is -x^{n/2} on s
+            let mut remainder = powered.iter().map(|x| -*x).collect::<Vec<_>>();
+            let mut inv_vanishing = remainder.clone();
+            parallel_single_low_degree_extention(
+                &mut inv_vanishing,
+                k,
+                0,
+                self.parallel_cutoff,
+                cache,
+            );
+            inv_vanishing
+                .par_iter_mut()
+                .zip(powered_prime.par_iter())
+                .for_each(|(r, x)| *r += x);
+            parallel_batch_inversion(&mut inv_vanishing);
+
+            // z^2 mod x^{n/2} = r^2 mod x^{n/2} = low^2 + x^{n/4} (2 low high mod x^{n/4})
+            // for r = low + x^{n/4} high whose products fit in half degree
+            self.exit(&mut remainder, k - 1);
+            let quarter = half >> 1;
+            let mut low = remainder[..quarter].to_vec();
+            let mut high = remainder[quarter..].to_vec();
+            low.resize(half, Fp::zero());
+            high.resize(half, Fp::zero());
+            let mut scratch = Scratch::new(k - 1);
+            self.par_enter(&mut low, scratch.get_mut(k - 1).1, k - 1);
+            self.par_enter(&mut high, scratch.get_mut(k - 1).1, k - 1);
+            let (mut squared, mut cross): (Vec<_>, Vec<_>) = low
+                .par_iter()
+                .zip(high.par_iter())
+                .map(|(low, high)| (low.square(), (*low * high).double()))
+                .unzip();
+            self.exit(&mut squared, k - 1);
+            self.exit(&mut cross, k - 1);
+            squared[quarter..]
+                .iter_mut()
+                .zip(cross.iter())
+                .for_each(|(square, cross)| *square += cross);
+
+            self.par_enter(&mut squared, scratch.get_mut(k - 1).1, k - 1);
+            let mut squared_prime = squared.clone();
+            parallel_single_low_degree_extention(
+                &mut squared_prime,
+                k,
+                0,
+                self.parallel_cutoff,
+                cache,
+            );
+
+            ExitParams {
+                reverse: cache.reverse_factors(),
+                inv_powered,
+                inv_vanishing,
+                squared: (squared, squared_prime),
+            }
+        })
+    }
+}
+
+fn mul_assign(values: &mut [Fp], factors: &[Fp]) {
+    values
+        .par_iter_mut()
+        .zip(factors.par_iter())
+        .for_each(|(value, factor)| *value *= factor);
+}
+
+// multiplication by ecfft over the base field of the size of each product
+// products larger than the largest coset are split into ones which fit it
+#[derive(Clone, Debug)]
+pub struct EcFftMultiplier {
+    ecfft: EcFft,
+}
+
+impl EcFftMultiplier {
+    // multiplier on the given params
+    pub fn new(ecfft: EcFft) -> Self {
+        EcFftMultiplier { ecfft }
+    }
+}
+
+impl Default for EcFftMultiplier {
+    // multiplier on the process-wide params
+    fn default() -> Self {
+        Self::new(EcFft::global().clone())
+    }
+}
+
+impl Multiplier<Fq> for EcFftMultiplier {
+    fn multiply(&self, a: &[Fq], b: &[Fq]) -> Vec<Fq> {
+        if let Some(product) = short_multiply(a, b) {
+            return product;
+        }
+
+        let n = a.len() + b.len() - 1;
+        if n > 1 << self.ecfft.max_k {
+            // a b = a_0 b + x^m a_1 b for the longer one a = a_0 + x^m a_1
+            let (a, b) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+            let m = a.len() >> 1;
+            let (mut product, high) =
+                join(|| self.multiply(&a[..m], b), || self.multiply(&a[m..], b));
+            product.resize(n, Fq::zero());
+            product[m..]
+                .iter_mut()
+                .zip(high.iter())
+                .for_each(|(c, high)| *c += high);
+            return product;
+        }
+
+        let k = n.next_power_of_two().trailing_zeros() as usize;
+        let (mut a, mut b) = (internal(a), internal(b));
+        a.resize(1 << k, Fp::zero());
+        b.resize(1 << k, Fp::zero());
+        let (mut scratch_a, mut scratch_b) = (Scratch::new(k), Scratch::new(k));
+        join(
+            || self.ecfft.par_enter(&mut a, scratch_a.get_mut(k).1, k),
+            || self.ecfft.par_enter(&mut b, scratch_b.get_mut(k).1, k),
+        );
+        mul_assign(&mut a, &b);
+        self.ecfft.exit(&mut a, k);
+        a[..n].par_iter().map(|c| Fq::from(*c)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EcFft, EcFftMultiplier};
+    use crate::polynomial::{Karatsuba, Multiplier};
+    use crate::test::{arb_poly, arb_poly_fq};
+    use pairing::bn256::Fq as Fp;
+
+    #[test]
+    fn test_interpolate() {
+        let ecfft = EcFft::new();
+        let mut parallel_ecfft = ecfft.clone();
+        parallel_ecfft.set_parallel_cutoff(1);
+
+        for k in 1..=11 {
+            let poly = arb_poly_fq(k);
+            let evals = ecfft.evaluate(k, poly.clone());
+            assert_eq!(ecfft.interpolate(k, evals.clone()), poly);
+            assert_eq!(parallel_ecfft.interpolate(k, evals), poly);
+        }
+
+        // exit params stay smaller than the params they are derived from
+        let tables = ecfft.memory_footprint();
+        for (k, size) in ecfft.derived_footprint() {
+            assert!(size < tables[k - 1].1);
+        }
+    }
+
+    #[test]
+    fn test_ecfft_multiplier() {
+        let multiplier = EcFftMultiplier::default();
+        for (n, m) in [(1, 3), (65, 65), (200, 70), (1000, 900), (9000, 8000)] {
+            let (a, b) = (arb_poly::<Fp>(n).values, arb_poly::<Fp>(m).values);
+            assert_eq!(multiplier.multiply(&a, &b), Karatsuba.multiply(&a, &b));
+        }
+
+        // half gcd over the base field
+        let poly = arb_poly::<Fp>;
+        let factor = poly(100);
+        let a = factor.clone().naive_multiply(poly(400));
+        let b = factor.naive_multiply(poly(350));
+        assert_eq!(a.fast_xgcd(&b, &multiplier), a.xgcd(&b));
+    }
+}
@@ -0,0 +1,299 @@
+// external memory ecfft for polynomials which don't fit in memory
+// polynomials are stored as consecutive canonical 32 bytes representations
+// nodes of the recursion which fit in the memory budget are evaluated in memory,
+// and larger ones are processed layer by layer streaming blocks between the data and a scratch
+
+use super::arithmetic::{parallel_low_degree_extention, serial_matrix_arithmetic};
+use super::field::Fp;
+use super::utils::EcFftCache;
+use super::EcFft;
+
+use pairing::group::ff::PrimeField;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+const ELEMENT_SIZE: usize = 32;
+
+impl EcFft {
+    // evaluate 2^k coefficients stored in data replacing them with evaluations
+    // scratch is used as temporary storage of the same size, and memory_budget bounds the bytes
+    // of polynomial blocks held in memory at once, not including precomputed params
+    pub fn evaluate_external<D, S>(
+        &self,
+        k: usize,
+        data: &mut D,
+        scratch: &mut S,
+        memory_budget: usize,
+    ) -> io::Result<()>
+    where
+        D: Read + Write + Seek,
+        S: Read + Write + Seek,
+    {
+        assert!((1..=self.max_k).contains(&k));
+        let budget = memory_budget / ELEMENT_SIZE;
+        // in memory evaluation of 2^b coefficients needs 3 * 2^b elements
+        let block_k = match log2_floor(budget / 3) {
+            Some(block_k) if block_k > 0 => block_k.min(k),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "memory budget is too small for ecfft blocks",
+                ))
+            }
+        };
+
+        // nodes fitting in the budget
+        let block_n = 1 << block_k;
+        let mut block_scratch = vec![Fp::zero(); 2 << block_k];
+        for offset in (0..1 << k).step_by(block_n) {
+            let mut values = read_elements(data, offset, block_n)?;
+            self.par_enter(&mut values, &mut block_scratch, block_k);
+            write_elements(data, offset, &values)?;
+        }
+
+        // larger nodes layer by layer
+        // streamed passes hold four chunks at once
+        let chunk = 1 << log2_floor(budget / 4).unwrap();
+        for m in (block_k + 1)..=k {
+            let cache = self.get_cache(m);
+            let (n, half_n) = (1 << m, 1 << (m - 1));
+            for node in (0..1 << k).step_by(n) {
+                let (low, high) = (node, node + half_n);
+                let chunk = chunk.min(half_n);
+
+                // even points depend on evaluations on s
+                integrate_external(data, scratch, cache, low, high, node, 0, chunk)?;
+                self.extend_external(data, cache, low, high, half_n, 0, budget, chunk)?;
+                // odd points depend on evaluations on s_prime
+                integrate_external(data, scratch, cache, low, high, high, 1, chunk)?;
+
+                // interleave even and odd points into data
+                for j in (0..half_n).step_by(chunk) {
+                    let even = read_elements(scratch, node + j, chunk)?;
+                    let odd = read_elements(scratch, high + j, chunk)?;
+                    let points = even
+                        .iter()
+                        .zip(odd.iter())
+                        .flat_map(|(even, odd)| [*even, *odd])
+                        .collect::<Vec<_>>();
+                    write_elements(data, node + 2 * j, &points)?;
+                }
+            }
+        }
+
+        data.flush()
+    }
+
+    // extend low and high vectors of n size from s to s_prime of the layer depth
+    #[allow(clippy::too_many_arguments)]
+    fn extend_external<D: Read + Write + Seek>(
+        &self,
+        data: &mut D,
+        cache: &EcFftCache,
+        low: usize,
+        high: usize,
+        n: usize,
+        depth: usize,
+        budget: usize,
+        chunk: usize,
+    ) -> io::Result<()> {
+        let k = n.trailing_zeros() as usize + 1;
+        if k == 1 {
+            return Ok(());
+        }
+
+        if 2 * n <= budget {
+            let mut coeffs = read_elements(data, low, n)?;
+            let mut coeffs_prime = read_elements(data, high, n)?;
+            parallel_low_degree_extention(
+                &mut coeffs,
+                &mut coeffs_prime,
+                n,
+                k,
+                depth,
+                self.parallel_cutoff,
+                cache,
+            );
+            write_elements(data, low, &coeffs)?;
+            return write_elements(data, high, &coeffs_prime);
+        }
+
+        let half_n = n >> 1;
+        let chunk = chunk.min(half_n);
+        let (factor, inv_factor) = cache.get_tree(depth).get_factors();
+        let convert = |data: &mut D, is_inverse: bool| -> io::Result<()> {
+            for i in (0..half_n).step_by(chunk) {
+                let offsets = [low + i, low + half_n + i, high + i, high + half_n + i];
+                let mut blocks = offsets
+                    .iter()
+                    .map(|offset| read_elements(data, *offset, chunk))
+                    .collect::<io::Result<Vec<_>>>()?;
+                let factor = if is_inverse { inv_factor } else { factor };
+                if let [left, right, left_prime, right_prime] = &mut blocks[..] {
+                    serial_matrix_arithmetic(
+                        left,
+                        right,
+                        left_prime,
+                        right_prime,
+                        factor.slice(i..i + chunk),
+                    );
+                }
+                for (offset, block) in offsets.iter().zip(blocks.iter()) {
+                    write_elements(data, *offset, block)?;
+                }
+            }
+            Ok(())
+        };
+
+        convert(data, true)?;
+        self.extend_external(data, cache, low, high, half_n, depth + 1, budget, chunk)?;
+        self.extend_external(
+            data,
+            cache,
+            low + half_n,
+            high + half_n,
+            half_n,
+            depth + 1,
+            budget,
+            chunk,
+        )?;
+        convert(data, false)
+    }
+}
+
+// write low + powered_coset * high on even or odd points of the node into scratch at offset
+#[allow(clippy::too_many_arguments)]
+fn integrate_external<D: Read + Seek, S: Write + Seek>(
+    data: &mut D,
+    scratch: &mut S,
+    cache: &EcFftCache,
+    low: usize,
+    high: usize,
+    offset: usize,
+    parity: usize,
+    chunk: usize,
+) -> io::Result<()> {
+    let half_n = high - low;
+    for j in (0..half_n).step_by(chunk) {
+        let low_values = read_elements(data, low + j, chunk)?;
+        let high_values = read_elements(data, high + j, chunk)?;
+        let points = low_values
+            .iter()
+            .zip(high_values.iter())
+            .zip(cache.powered_coset[2 * j + parity..].iter().step_by(2))
+            .map(|((low, high), power)| low + power * high)
+            .collect::<Vec<_>>();
+        write_elements(scratch, offset + j, &points)?;
+    }
+    Ok(())
+}
+
+fn log2_floor(x: usize) -> Option<usize> {
+    match x {
+        0 => None,
+        _ => Some((usize::BITS - 1 - x.leading_zeros()) as usize),
+    }
+}
+
+fn read_elements<R: Read + Seek>(
+    reader: &mut R,
+    offset: usize,
+    size: usize,
+) -> io::Result<Vec<Fp>> {
+    reader.seek(SeekFrom::Start((offset * ELEMENT_SIZE) as u64))?;
+    let mut bytes = vec![0u8; size * ELEMENT_SIZE];
+    reader.read_exact(&mut bytes)?;
+    bytes
+        .chunks(ELEMENT_SIZE)
+        .map(|bytes| {
+            let mut repr = <Fp as PrimeField>::Repr::default();
+            repr.as_mut().copy_from_slice(bytes);
+            Option::from(Fp::from_repr(repr)).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "non canonical field element")
+            })
+        })
+        .collect()
+}
+
+fn write_elements<W: Write + Seek>(
+    writer: &mut W,
+    offset: usize,
+    elements: &[Fp],
+) -> io::Result<()> {
+    writer.seek(SeekFrom::Start((offset * ELEMENT_SIZE) as u64))?;
+    let bytes = elements
+        .iter()
+        .flat_map(|element| element.to_repr().as_ref().to_vec())
+        .collect::<Vec<_>>();
+    writer.write_all(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_elements, write_elements, ELEMENT_SIZE};
+    use crate::ecfft::internal;
+    use crate::test::arb_poly_fq;
+    use crate::EcFft;
+    use std::fs::OpenOptions;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_evaluate_external() {
+        let ecfft = EcFft::new();
+
+        for k in 1..11 {
+            let poly = arb_poly_fq(k);
+            let expected = internal(&ecfft.evaluate(k, poly.clone()).get_values());
+
+            // smallest budget, streamed layers and in memory extension, whole polynomial
+            for elements in [6, 16, 1 << 8, 3 << k] {
+                let mut data = Cursor::new(Vec::new());
+                let mut scratch = Cursor::new(Vec::new());
+                write_elements(&mut data, 0, &internal(&poly.values)).unwrap();
+
+                ecfft
+                    .evaluate_external(k, &mut data, &mut scratch, elements * ELEMENT_SIZE)
+                    .unwrap();
+                assert_eq!(read_elements(&mut data, 0, 1 << k).unwrap(), expected);
+            }
+        }
+
+        let mut data = Cursor::new(Vec::new());
+        assert!(ecfft
+            .evaluate_external(3, &mut data, &mut Cursor::new(Vec::new()), 64)
+            .is_err());
+    }
+
+    #[test]
+    fn test_evaluate_file() {
+        let k = 12;
+        let ecfft = EcFft::new_compact();
+        let poly = arb_poly_fq(k);
+        let dir = std::env::temp_dir();
+        let paths = [
+            dir.join(format!("ecfft_data_{}", std::process::id())),
+            dir.join(format!("ecfft_scratch_{}", std::process::id())),
+        ];
+        let open = |path| {
+            OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(path)
+                .unwrap()
+        };
+        let (mut data, mut scratch) = (open(&paths[0]), open(&paths[1]));
+        write_elements(&mut data, 0, &internal(&poly.values)).unwrap();
+
+        // 256 elements of 4096 in memory
+        ecfft
+            .evaluate_external(k, &mut data, &mut scratch, 256 * ELEMENT_SIZE)
+            .unwrap();
+        let evaluations = read_elements(&mut data, 0, 1 << k).unwrap();
+        paths
+            .iter()
+            .for_each(|path| std::fs::remove_file(path).unwrap());
+
+        assert_eq!(evaluations, internal(&ecfft.evaluate(k, poly).get_values()));
+    }
+}
@@ -1,38 +1,205 @@
-use pairing::bn256::Fq as Fp;
+use super::field::Fp;
 
-#[derive(Clone, Debug)]
+use std::borrow::Cow;
+use std::mem::size_of;
+use std::ops::Range;
+
+// field elements embedded in the binary or computed at runtime
+pub(crate) type Table = Cow<'static, [Fp]>;
+
+// 2x2 matrices stored entry by entry in separate arrays
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum FactorMatrix {
+    // every entry [[f0, f1], [f2, f3]] of matrices
+    Full([Table; 4]),
+    // two scalars of each matrix, the other entries are derived from the domain
+    Compact([Table; 2]),
+}
+
+impl FactorMatrix {
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            FactorMatrix::Full(entries) => entries[0].len(),
+            FactorMatrix::Compact(scalars) => scalars[0].len(),
+        }
+    }
+
+    // borrow matrices with the domain compact ones are defined on
+    pub(crate) fn view<'a>(&'a self, domain: &'a [Fp], is_inverse: bool) -> FactorView<'a> {
+        match self {
+            FactorMatrix::Full([f0, f1, f2, f3]) => FactorView::Full([f0, f1, f2, f3]),
+            FactorMatrix::Compact([p, q]) => {
+                let (a, b) = domain.split_at(p.len());
+                if is_inverse {
+                    FactorView::CompactInverse { u: p, w: q, a, b }
+                } else {
+                    FactorView::Compact { p, q, a, b }
+                }
+            }
+        }
+    }
+
+    // heap size of entries in bytes
+    pub(crate) fn memory_size(&self) -> usize {
+        match self {
+            FactorMatrix::Full(_) => 4 * self.len() * size_of::<Fp>(),
+            FactorMatrix::Compact(_) => 2 * self.len() * size_of::<Fp>(),
+        }
+    }
+}
+
+impl From<Vec<((Fp, Fp), (Fp, Fp))>> for FactorMatrix {
+    fn from(matrices: Vec<((Fp, Fp), (Fp, Fp))>) -> Self {
+        let mut entries: [Vec<Fp>; 4] = Default::default();
+        entries
+            .iter_mut()
+            .for_each(|entry| entry.reserve_exact(matrices.len()));
+        matrices.into_iter().for_each(|((f0, f1), (f2, f3))| {
+            entries[0].push(f0);
+            entries[1].push(f1);
+            entries[2].push(f2);
+            entries[3].push(f3);
+        });
+        FactorMatrix::Full(entries.map(Cow::Owned))
+    }
+}
+
+// factor matrices borrowed with the domain they are defined on
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum FactorView<'a> {
+    // [[f0, f1], [f2, f3]]
+    Full([&'a [Fp]; 4]),
+    // [[p, a * p], [q, b * q]] on domain (a, b)
+    Compact {
+        p: &'a [Fp],
+        q: &'a [Fp],
+        a: &'a [Fp],
+        b: &'a [Fp],
+    },
+    // [[b * u, -a * w], [-u, w]] on domain (a, b)
+    // inverse of the compact matrix with u = 1 / p(b - a) and w = 1 / q(b - a)
+    CompactInverse {
+        u: &'a [Fp],
+        w: &'a [Fp],
+        a: &'a [Fp],
+        b: &'a [Fp],
+    },
+}
+
+impl<'a> FactorView<'a> {
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            FactorView::Full(entries) => entries[0].len(),
+            FactorView::Compact { p, .. } => p.len(),
+            FactorView::CompactInverse { u, .. } => u.len(),
+        }
+    }
+
+    // matrices of the index range
+    pub(crate) fn slice(&self, range: Range<usize>) -> Self {
+        match self {
+            FactorView::Full([f0, f1, f2, f3]) => FactorView::Full([
+                &f0[range.clone()],
+                &f1[range.clone()],
+                &f2[range.clone()],
+                &f3[range],
+            ]),
+            FactorView::Compact { p, q, a, b } => FactorView::Compact {
+                p: &p[range.clone()],
+                q: &q[range.clone()],
+                a: &a[range.clone()],
+                b: &b[range],
+            },
+            FactorView::CompactInverse { u, w, a, b } => FactorView::CompactInverse {
+                u: &u[range.clone()],
+                w: &w[range.clone()],
+                a: &a[range.clone()],
+                b: &b[range],
+            },
+        }
+    }
+
+    // i-th matrix with every entry
+    pub(crate) fn entry(&self, i: usize) -> ((Fp, Fp), (Fp, Fp)) {
+        match self {
+            FactorView::Full([f0, f1, f2, f3]) => ((f0[i], f1[i]), (f2[i], f3[i])),
+            FactorView::Compact { p, q, a, b } => ((p[i], a[i] * p[i]), (q[i], b[i] * q[i])),
+            FactorView::CompactInverse { u, w, a, b } => {
+                ((b[i] * u[i], -(a[i] * w[i])), (-u[i], w[i]))
+            }
+        }
+    }
+}
+
+// factors of every layer which extend evaluations on s to s_prime
+pub(crate) trait Extension: Sync {
+    // factor onto s_prime and inverse factor from s of the layer of depth
+    fn get_factors(&self, depth: usize) -> (FactorView<'_>, FactorView<'_>);
+
+    // the same of the bottom layer whose s and s_prime are single points
+    fn get_last_tree(&self) -> (((Fp, Fp), (Fp, Fp)), ((Fp, Fp), (Fp, Fp)));
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub(crate) struct FfTree {
     // evaluation domain same size with polynomial
-    pub(crate) domain: (Vec<Fp>, Vec<Fp>),
-    // factor for performing multiplication
-    pub(crate) factor: Vec<((Fp, Fp), (Fp, Fp))>,
-    // inverse factor for performing multiplication
-    pub(crate) inv_factor: Vec<((Fp, Fp), (Fp, Fp))>,
+    pub(crate) domain: (Table, Table),
+    // factor for performing multiplication on s_prime
+    pub(crate) factor: FactorMatrix,
+    // inverse factor for performing multiplication on s
+    pub(crate) inv_factor: FactorMatrix,
 }
 
 impl FfTree {
-    #[cfg(test)]
-    pub(crate) fn get_domain(&self) -> &(Vec<Fp>, Vec<Fp>) {
-        &self.domain
+    pub(crate) fn get_factors(&self) -> (FactorView<'_>, FactorView<'_>) {
+        (self.get_factor(), self.get_inv_factor())
     }
 
-    pub(crate) fn get_factors(&self) -> (&Vec<((Fp, Fp), (Fp, Fp))>, &Vec<((Fp, Fp), (Fp, Fp))>) {
-        (&self.factor, &self.inv_factor)
+    pub(crate) fn get_factor(&self) -> FactorView<'_> {
+        self.factor.view(&self.domain.1, false)
     }
 
-    pub(crate) fn get_factor(&self) -> &Vec<((Fp, Fp), (Fp, Fp))> {
-        &self.factor
+    pub(crate) fn get_inv_factor(&self) -> FactorView<'_> {
+        self.inv_factor.view(&self.domain.0, true)
+    }
+
+    // keep two scalars of each matrix deriving the others from the domain
+    pub(crate) fn compact(self) -> Self {
+        let FfTree {
+            domain,
+            factor,
+            inv_factor,
+        } = self;
+        let factor = match factor {
+            FactorMatrix::Full([p, _, q, _]) => FactorMatrix::Compact([p, q]),
+            compact => compact,
+        };
+        let inv_factor = match inv_factor {
+            FactorMatrix::Full([_, _, minus_u, w]) => {
+                FactorMatrix::Compact([minus_u.iter().map(|u| -*u).collect(), w])
+            }
+            compact => compact,
+        };
+        FfTree {
+            domain,
+            factor,
+            inv_factor,
+        }
     }
 
-    pub(crate) fn get_inv_factor(&self) -> &Vec<((Fp, Fp), (Fp, Fp))> {
-        &self.inv_factor
+    // heap size of domain and factors in bytes
+    pub(crate) fn memory_size(&self) -> usize {
+        let (s, s_prime) = &self.domain;
+        (s.len() + s_prime.len()) * size_of::<Fp>()
+            + self.factor.memory_size()
+            + self.inv_factor.memory_size()
     }
 
     pub(crate) fn last_tree(s: Vec<Fp>, s_prime: Vec<Fp>) -> Self {
         FfTree {
-            domain: (s, s_prime),
-            factor: vec![],
-            inv_factor: vec![],
+            domain: (s.into(), s_prime.into()),
+            factor: FactorMatrix::Full(Default::default()),
+            inv_factor: FactorMatrix::Full(Default::default()),
         }
     }
 }
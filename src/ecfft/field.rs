@@ -0,0 +1,842 @@
+// base field of the ecfft owned by the crate so that the kernels can work on its limbs
+// elements are in montgomery form with R = 2^256 and are converted from and into the pairing
+// field only where values cross the public api
+use pairing::bn256::Fq;
+use pairing::group::ff::{Field, PrimeField};
+use rand::RngCore;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
+
+// base field modulus
+const MODULUS: [u64; 4] = [
+    0x3c208c16d87cfd47,
+    0x97816a916871ca8d,
+    0xb85045b68181585d,
+    0x30644e72e131a029,
+];
+
+// -q^{-1} mod 2^64
+const INV: u64 = 0x87d20782e4866389;
+
+// 2^256 mod q which is one in montgomery form
+const R: [u64; 4] = [
+    0xd35d438dc58f0d9d,
+    0x0a78eb28f5c70b3d,
+    0x666ea36f7879462c,
+    0x0e0a77c19a07df2f,
+];
+
+// 2^512 mod q, montgomery multiplication by this converts canonical limbs into montgomery form
+const R2: [u64; 4] = [
+    0xf32cfc5b538afa89,
+    0xb5e71911d44501fb,
+    0x47ab1eff0a417ff6,
+    0x06d89f71cab8351f,
+];
+
+// q - 2 for inversion by fermat's little theorem
+const MODULUS_MINUS_TWO: [u64; 4] = [
+    0x3c208c16d87cfd45,
+    0x97816a916871ca8d,
+    0xb85045b68181585d,
+    0x30644e72e131a029,
+];
+
+// (q + 1) / 4 for square roots as q = 3 mod 4
+const SQRT_EXPONENT: [u64; 4] = [
+    0x4f082305b61f3f52,
+    0x65e05aa45a1c72a3,
+    0x6e14116da0605617,
+    0x0c19139cb84c680a,
+];
+
+// element a R mod q held as little-endian limbs
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub(crate) struct Fp(pub(crate) [u64; 4]);
+
+impl Fp {
+    pub(crate) const fn zero() -> Fp {
+        Fp([0, 0, 0, 0])
+    }
+
+    pub(crate) const fn one() -> Fp {
+        Fp(R)
+    }
+
+    // element of canonical limbs which must be less than the modulus
+    pub(crate) const fn from_raw(limbs: [u64; 4]) -> Fp {
+        Fp(limbs).montgomery_mul(&Fp(R2))
+    }
+
+    // canonical little-endian limbs
+    pub(crate) const fn to_raw(self) -> [u64; 4] {
+        let l = self.0;
+        montgomery_reduce([l[0], l[1], l[2], l[3], 0, 0, 0, 0]).0
+    }
+
+    const fn montgomery_mul(&self, rhs: &Fp) -> Fp {
+        montgomery_reduce(mul_wide(&self.0, &rhs.0))
+    }
+
+    const fn add_mod(&self, rhs: &Fp) -> Fp {
+        // the sum is less than 2q < 2^256 so it never carries out
+        let mut sum = [0u64; 4];
+        let mut carry = 0;
+        let mut i = 0;
+        while i < 4 {
+            let (limb, next) = adc(self.0[i], rhs.0[i], carry);
+            sum[i] = limb;
+            carry = next;
+            i += 1;
+        }
+        Fp(subtract_modulus(sum, 0))
+    }
+
+    const fn sub_mod(&self, rhs: &Fp) -> Fp {
+        let mut diff = [0u64; 4];
+        let mut borrow = 0;
+        let mut i = 0;
+        while i < 4 {
+            let (limb, next) = sbb(self.0[i], rhs.0[i], borrow);
+            diff[i] = limb;
+            borrow = next;
+            i += 1;
+        }
+        if borrow == 0 {
+            return Fp(diff);
+        }
+
+        let mut carry = 0;
+        let mut i = 0;
+        while i < 4 {
+            let (limb, next) = adc(diff[i], MODULUS[i], carry);
+            diff[i] = limb;
+            carry = next;
+            i += 1;
+        }
+        Fp(diff)
+    }
+
+    const fn neg_mod(&self) -> Fp {
+        Fp::zero().sub_mod(self)
+    }
+}
+
+// 512 bit product of 256 bit integers
+const fn mul_wide(a: &[u64; 4], b: &[u64; 4]) -> [u64; 8] {
+    let mut product = [0u64; 8];
+    let mut i = 0;
+    while i < 4 {
+        let mut carry = 0;
+        let mut j = 0;
+        while j < 4 {
+            let (lo, hi) = mac(product[i + j], a[i], b[j], carry);
+            product[i + j] = lo;
+            carry = hi;
+            j += 1;
+        }
+        product[i + 4] = carry;
+        i += 1;
+    }
+    product
+}
+
+// t 2^-256 mod q for t less than q 2^256
+const fn montgomery_reduce(mut t: [u64; 8]) -> Fp {
+    let mut carry2 = 0;
+    let mut i = 0;
+    while i < 4 {
+        let m = t[i].wrapping_mul(INV);
+        let mut carry = 0;
+        let mut j = 0;
+        while j < 4 {
+            let (lo, hi) = mac(t[i + j], m, MODULUS[j], carry);
+            t[i + j] = lo;
+            carry = hi;
+            j += 1;
+        }
+        let (sum, next) = adc(t[i + 4], carry2, carry);
+        t[i + 4] = sum;
+        carry2 = next;
+        i += 1;
+    }
+    Fp(subtract_modulus([t[4], t[5], t[6], t[7]], carry2))
+}
+
+// value of limbs and the top bit which is less than 2q reduced by subtracting q once
+const fn subtract_modulus(limbs: [u64; 4], top: u64) -> [u64; 4] {
+    let mut reduced = [0u64; 4];
+    let mut borrow = 0;
+    let mut i = 0;
+    while i < 4 {
+        let (diff, next) = sbb(limbs[i], MODULUS[i], borrow);
+        reduced[i] = diff;
+        borrow = next;
+        i += 1;
+    }
+    let (_, borrow) = sbb(top, 0, borrow);
+    if borrow == 0 {
+        reduced
+    } else {
+        limbs
+    }
+}
+
+// a + b * c + carry as (low, high)
+const fn mac(a: u64, b: u64, c: u64, carry: u64) -> (u64, u64) {
+    let ret = (a as u128) + ((b as u128) * (c as u128)) + (carry as u128);
+    (ret as u64, (ret >> 64) as u64)
+}
+
+// a + b + carry as (sum, carry)
+const fn adc(a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let ret = (a as u128) + (b as u128) + (carry as u128);
+    (ret as u64, (ret >> 64) as u64)
+}
+
+// a - b - borrow as (difference, borrow)
+const fn sbb(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+    let ret = (a as u128).wrapping_sub((b as u128) + (borrow as u128));
+    (ret as u64, (ret >> 127) as u64)
+}
+
+// a0 * b0 + a1 * b1 reducing once
+pub(crate) fn sum_of_products(a0: Fp, b0: Fp, a1: Fp, b1: Fp) -> Fp {
+    let mut acc = WideFp::default();
+    acc.add_mul(a0, b0);
+    acc.add_mul(a1, b1);
+    acc.reduce()
+}
+
+// unreduced sum of products of montgomery limbs as 512 bit integer
+// each product is less than q^2 and montgomery reduction needs less than q * 2^256,
+// so up to five products can be accumulated
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct WideFp([u64; 8]);
+
+impl WideFp {
+    pub(crate) fn add_mul(&mut self, a: Fp, b: Fp) {
+        let product = mul_wide(&a.0, &b.0);
+        let mut carry = 0;
+        for (limb, product) in self.0.iter_mut().zip(product.iter()) {
+            let (sum, next) = adc(*limb, *product, carry);
+            *limb = sum;
+            carry = next;
+        }
+    }
+
+    // a R b R 2^-256 = a b R so the accumulated value is in montgomery form after one reduction
+    pub(crate) fn reduce(self) -> Fp {
+        montgomery_reduce(self.0)
+    }
+}
+
+impl From<Fq> for Fp {
+    fn from(element: Fq) -> Fp {
+        let repr = element.to_repr();
+        let mut limbs = [0u64; 4];
+        for (limb, bytes) in limbs.iter_mut().zip(repr.as_ref().chunks(8)) {
+            let mut word = [0u8; 8];
+            word.copy_from_slice(bytes);
+            *limb = u64::from_le_bytes(word);
+        }
+        Fp::from_raw(limbs)
+    }
+}
+
+impl From<Fp> for Fq {
+    fn from(element: Fp) -> Fq {
+        Fq::from_raw(element.to_raw())
+    }
+}
+
+impl From<u64> for Fp {
+    fn from(value: u64) -> Fp {
+        Fp::from_raw([value, 0, 0, 0])
+    }
+}
+
+impl fmt::Debug for Fp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x")?;
+        for limb in self.to_raw().iter().rev() {
+            write!(f, "{:016x}", limb)?;
+        }
+        Ok(())
+    }
+}
+
+// ordered by canonical value as the pairing field
+impl PartialOrd for Fp {
+    fn partial_cmp(&self, other: &Fp) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Fp {
+    fn cmp(&self, other: &Fp) -> Ordering {
+        self.to_raw().iter().rev().cmp(other.to_raw().iter().rev())
+    }
+}
+
+impl ConstantTimeEq for Fp {
+    fn ct_eq(&self, other: &Fp) -> Choice {
+        self.0[0].ct_eq(&other.0[0])
+            & self.0[1].ct_eq(&other.0[1])
+            & self.0[2].ct_eq(&other.0[2])
+            & self.0[3].ct_eq(&other.0[3])
+    }
+}
+
+impl ConditionallySelectable for Fp {
+    fn conditional_select(a: &Fp, b: &Fp, choice: Choice) -> Fp {
+        Fp([
+            u64::conditional_select(&a.0[0], &b.0[0], choice),
+            u64::conditional_select(&a.0[1], &b.0[1], choice),
+            u64::conditional_select(&a.0[2], &b.0[2], choice),
+            u64::conditional_select(&a.0[3], &b.0[3], choice),
+        ])
+    }
+}
+
+impl Neg for Fp {
+    type Output = Fp;
+
+    fn neg(self) -> Fp {
+        self.neg_mod()
+    }
+}
+
+impl Neg for &Fp {
+    type Output = Fp;
+
+    fn neg(self) -> Fp {
+        self.neg_mod()
+    }
+}
+
+macro_rules! impl_binop {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $op:ident) => {
+        impl $trait<Fp> for Fp {
+            type Output = Fp;
+
+            fn $method(self, rhs: Fp) -> Fp {
+                self.$op(&rhs)
+            }
+        }
+
+        impl<'b> $trait<&'b Fp> for Fp {
+            type Output = Fp;
+
+            fn $method(self, rhs: &'b Fp) -> Fp {
+                self.$op(rhs)
+            }
+        }
+
+        impl<'a> $trait<Fp> for &'a Fp {
+            type Output = Fp;
+
+            fn $method(self, rhs: Fp) -> Fp {
+                self.$op(&rhs)
+            }
+        }
+
+        impl<'a, 'b> $trait<&'b Fp> for &'a Fp {
+            type Output = Fp;
+
+            fn $method(self, rhs: &'b Fp) -> Fp {
+                self.$op(rhs)
+            }
+        }
+
+        impl $assign_trait<Fp> for Fp {
+            fn $assign_method(&mut self, rhs: Fp) {
+                *self = self.$op(&rhs);
+            }
+        }
+
+        impl<'b> $assign_trait<&'b Fp> for Fp {
+            fn $assign_method(&mut self, rhs: &'b Fp) {
+                *self = self.$op(rhs);
+            }
+        }
+    };
+}
+
+impl_binop!(Add, add, AddAssign, add_assign, add_mod);
+impl_binop!(Sub, sub, SubAssign, sub_assign, sub_mod);
+impl_binop!(Mul, mul, MulAssign, mul_assign, montgomery_mul);
+
+impl Field for Fp {
+    fn random(rng: impl RngCore) -> Fp {
+        Fq::random(rng).into()
+    }
+
+    fn zero() -> Fp {
+        Fp::zero()
+    }
+
+    fn one() -> Fp {
+        Fp::one()
+    }
+
+    fn square(&self) -> Fp {
+        self.montgomery_mul(self)
+    }
+
+    fn double(&self) -> Fp {
+        self.add_mod(self)
+    }
+
+    fn invert(&self) -> CtOption<Fp> {
+        CtOption::new(self.pow_vartime(MODULUS_MINUS_TWO), !self.is_zero())
+    }
+
+    fn sqrt(&self) -> CtOption<Fp> {
+        let root = self.pow_vartime(SQRT_EXPONENT);
+        CtOption::new(root, root.square().ct_eq(self))
+    }
+}
+
+impl PrimeField for Fp {
+    type Repr = [u8; 32];
+
+    const NUM_BITS: u32 = 254;
+    const CAPACITY: u32 = 253;
+    const S: u32 = 1;
+
+    fn from_repr(repr: [u8; 32]) -> CtOption<Fp> {
+        let mut limbs = [0u64; 4];
+        for (limb, bytes) in limbs.iter_mut().zip(repr.chunks(8)) {
+            let mut word = [0u8; 8];
+            word.copy_from_slice(bytes);
+            *limb = u64::from_le_bytes(word);
+        }
+        let mut borrow = 0;
+        for (limb, modulus) in limbs.iter().zip(MODULUS.iter()) {
+            borrow = sbb(*limb, *modulus, borrow).1;
+        }
+        let is_canonical = Choice::from(borrow as u8);
+        let element = Fp::conditional_select(&Fp::zero(), &Fp::from_raw(limbs), is_canonical);
+        CtOption::new(element, is_canonical)
+    }
+
+    fn to_repr(&self) -> [u8; 32] {
+        let mut repr = [0u8; 32];
+        for (bytes, limb) in repr.chunks_mut(8).zip(self.to_raw().iter()) {
+            bytes.copy_from_slice(&limb.to_le_bytes());
+        }
+        repr
+    }
+
+    fn is_odd(&self) -> Choice {
+        Choice::from((self.to_raw()[0] & 1) as u8)
+    }
+
+    fn multiplicative_generator() -> Fp {
+        Fp::from(3)
+    }
+
+    // q - 1 = 2 t for odd t so -1 generates the 2^S roots of unity
+    fn root_of_unity() -> Fp {
+        -Fp::one()
+    }
+}
+
+// avx2 kernels whose support is detected once per kernel by taking this token
+// only sums of products are vectorised as a single product is faster with scalar limbs
+#[cfg(feature = "simd")]
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Simd(());
+
+#[cfg(feature = "simd")]
+impl Simd {
+    pub(crate) fn detect() -> Option<Simd> {
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx2") {
+            return Some(Simd(()));
+        }
+        None
+    }
+
+    // (l, r) <- (f0 l + f1 r, f2 l + f3 r) over four pairs at once reducing each output once
+    pub(crate) fn matrix_arithmetic(
+        self,
+        left: &mut [Fp],
+        right: &mut [Fp],
+        [f0, f1, f2, f3]: [&[Fp]; 4],
+    ) {
+        #[cfg(target_arch = "x86_64")]
+        left.chunks_exact_mut(4)
+            .zip(right.chunks_exact_mut(4))
+            .zip(f0.chunks_exact(4).zip(f1.chunks_exact(4)))
+            .zip(f2.chunks_exact(4).zip(f3.chunks_exact(4)))
+            .for_each(|(((l, r), (f0, f1)), (f2, f3))| unsafe {
+                avx2::matrix_arithmetic(l, r, [f0, f1, f2, f3])
+            });
+
+        let len = left.len() - left.len() % 4;
+        left[len..]
+            .iter_mut()
+            .zip(right[len..].iter_mut())
+            .zip(f0[len..].iter().zip(f1[len..].iter()))
+            .zip(f2[len..].iter().zip(f3[len..].iter()))
+            .for_each(|(((l, r), (f0, f1)), (f2, f3))| {
+                let tmp = sum_of_products(*f2, *l, *f3, *r);
+                *l = sum_of_products(*f0, *l, *f1, *r);
+                *r = tmp;
+            });
+    }
+
+    // the same matrix on every pair
+    pub(crate) fn uniform_matrix_arithmetic(
+        self,
+        left: &mut [Fp],
+        right: &mut [Fp],
+        [f0, f1, f2, f3]: [Fp; 4],
+    ) {
+        #[cfg(target_arch = "x86_64")]
+        unsafe {
+            avx2::uniform_matrix_arithmetic(left, right, [f0, f1, f2, f3])
+        };
+
+        let len = left.len() - left.len() % 4;
+        left[len..]
+            .iter_mut()
+            .zip(right[len..].iter_mut())
+            .for_each(|(l, r)| {
+                let tmp = sum_of_products(f2, *l, f3, *r);
+                *l = sum_of_products(f0, *l, f1, *r);
+                *r = tmp;
+            });
+    }
+}
+
+// montgomery arithmetic of four elements held as 32 bit limbs in 64 bit lanes
+// the limbs are the montgomery ones of the scalar field so results match bit for bit
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod avx2 {
+    use super::{Fp, INV, MODULUS};
+
+    use std::arch::x86_64::*;
+
+    const MASK: i64 = 0xffff_ffff;
+
+    // i-th vector has i-th 32 bit limb of every element
+    type Limbs = [__m256i; 8];
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn load(elements: [&Fp; 4]) -> Limbs {
+        let mask = _mm256_set1_epi64x(MASK);
+        let mut limbs = [_mm256_setzero_si256(); 8];
+        for i in 0..4 {
+            let words = _mm256_set_epi64x(
+                elements[3].0[i] as i64,
+                elements[2].0[i] as i64,
+                elements[1].0[i] as i64,
+                elements[0].0[i] as i64,
+            );
+            limbs[2 * i] = _mm256_and_si256(words, mask);
+            limbs[2 * i + 1] = _mm256_srli_epi64(words, 32);
+        }
+        limbs
+    }
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn store(limbs: &Limbs, elements: &mut [Fp]) {
+        let mut lanes = [[0u64; 4]; 8];
+        for (lane, limb) in lanes.iter_mut().zip(limbs.iter()) {
+            _mm256_storeu_si256(lane.as_mut_ptr() as *mut __m256i, *limb);
+        }
+        for (i, element) in elements.iter_mut().enumerate() {
+            for j in 0..4 {
+                element.0[j] = lanes[2 * j][i] | lanes[2 * j + 1][i] << 32;
+            }
+        }
+    }
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn constant(words: &[u64; 4]) -> Limbs {
+        let mut limbs = [_mm256_setzero_si256(); 8];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            *limb = _mm256_set1_epi64x(((words[i / 2] >> (32 * (i % 2))) & 0xffff_ffff) as i64);
+        }
+        limbs
+    }
+
+    // four pairs of slices of four elements
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn matrix_arithmetic(
+        left: &mut [Fp],
+        right: &mut [Fp],
+        [f0, f1, f2, f3]: [&[Fp]; 4],
+    ) {
+        let modulus = constant(&MODULUS);
+        let factor = |f: &[Fp]| load([&f[0], &f[1], &f[2], &f[3]]);
+        matrix_product(
+            left,
+            right,
+            &[factor(f0), factor(f1), factor(f2), factor(f3)],
+            &modulus,
+        );
+    }
+
+    // every four pairs of slices with the matrix broadcast to all lanes once
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn uniform_matrix_arithmetic(
+        left: &mut [Fp],
+        right: &mut [Fp],
+        factor: [Fp; 4],
+    ) {
+        let modulus = constant(&MODULUS);
+        let factor = factor.map(|f| constant(&f.0));
+        left.chunks_exact_mut(4)
+            .zip(right.chunks_exact_mut(4))
+            .for_each(|(l, r)| matrix_product(l, r, &factor, &modulus));
+    }
+
+    // sums of two products are accumulated unreduced and reduced once
+    #[target_feature(enable = "avx2")]
+    unsafe fn matrix_product(
+        left: &mut [Fp],
+        right: &mut [Fp],
+        [f0, f1, f2, f3]: &[Limbs; 4],
+        modulus: &Limbs,
+    ) {
+        let l = load([&left[0], &left[1], &left[2], &left[3]]);
+        let r = load([&right[0], &right[1], &right[2], &right[3]]);
+
+        let mut acc = mul_wide(f0, &l);
+        add_wide(&mut acc, &mul_wide(f1, &r));
+        let mut acc_prime = mul_wide(f2, &l);
+        add_wide(&mut acc_prime, &mul_wide(f3, &r));
+
+        store(&montgomery_reduce(acc, modulus), left);
+        store(&montgomery_reduce(acc_prime, modulus), right);
+    }
+
+    // 512 bit products as 16 limbs of 32 bits
+    #[target_feature(enable = "avx2")]
+    unsafe fn mul_wide(a: &Limbs, b: &Limbs) -> [__m256i; 16] {
+        let mask = _mm256_set1_epi64x(MASK);
+        let mut t = [_mm256_setzero_si256(); 16];
+        for (i, b_i) in b.iter().enumerate() {
+            let mut carry = _mm256_setzero_si256();
+            for (j, a_j) in a.iter().enumerate() {
+                let s = _mm256_add_epi64(
+                    _mm256_add_epi64(t[i + j], _mm256_mul_epu32(*a_j, *b_i)),
+                    carry,
+                );
+                t[i + j] = _mm256_and_si256(s, mask);
+                carry = _mm256_srli_epi64(s, 32);
+            }
+            t[i + 8] = carry;
+        }
+        t
+    }
+
+    // sum of products less than 2^512 does not carry out
+    #[target_feature(enable = "avx2")]
+    unsafe fn add_wide(acc: &mut [__m256i; 16], t: &[__m256i; 16]) {
+        let mask = _mm256_set1_epi64x(MASK);
+        let mut carry = _mm256_setzero_si256();
+        for (limb, t) in acc.iter_mut().zip(t.iter()) {
+            let s = _mm256_add_epi64(_mm256_add_epi64(*limb, *t), carry);
+            *limb = _mm256_and_si256(s, mask);
+            carry = _mm256_srli_epi64(s, 32);
+        }
+    }
+
+    // t 2^-256 mod q over 32 bit limbs for t less than q 2^256
+    #[target_feature(enable = "avx2")]
+    unsafe fn montgomery_reduce(mut t: [__m256i; 16], modulus: &Limbs) -> Limbs {
+        let mask = _mm256_set1_epi64x(MASK);
+        let inv = _mm256_set1_epi64x((INV & 0xffff_ffff) as i64);
+        let mut carry2 = _mm256_setzero_si256();
+        for i in 0..8 {
+            let m = _mm256_and_si256(_mm256_mul_epu32(t[i], inv), mask);
+            let mut carry =
+                _mm256_srli_epi64(_mm256_add_epi64(t[i], _mm256_mul_epu32(m, modulus[0])), 32);
+            for j in 1..8 {
+                let s = _mm256_add_epi64(
+                    _mm256_add_epi64(t[i + j], _mm256_mul_epu32(m, modulus[j])),
+                    carry,
+                );
+                t[i + j] = _mm256_and_si256(s, mask);
+                carry = _mm256_srli_epi64(s, 32);
+            }
+            let s = _mm256_add_epi64(_mm256_add_epi64(t[i + 8], carry2), carry);
+            t[i + 8] = _mm256_and_si256(s, mask);
+            carry2 = _mm256_srli_epi64(s, 32);
+        }
+        let mut limbs = [_mm256_setzero_si256(); 8];
+        limbs.copy_from_slice(&t[8..]);
+        subtract_modulus(&limbs, carry2, modulus)
+    }
+
+    // value of limbs and top less than 2q reduced by subtracting q if there is no borrow
+    #[target_feature(enable = "avx2")]
+    unsafe fn subtract_modulus(limbs: &Limbs, top: __m256i, modulus: &Limbs) -> Limbs {
+        let mask = _mm256_set1_epi64x(MASK);
+        let base = _mm256_set1_epi64x(1 << 32);
+        let one = _mm256_set1_epi64x(1);
+        let mut borrow = _mm256_setzero_si256();
+        let mut reduced = [_mm256_setzero_si256(); 8];
+        for j in 0..8 {
+            let s = _mm256_sub_epi64(
+                _mm256_sub_epi64(_mm256_add_epi64(limbs[j], base), modulus[j]),
+                borrow,
+            );
+            reduced[j] = _mm256_and_si256(s, mask);
+            borrow = _mm256_sub_epi64(one, _mm256_srli_epi64(s, 32));
+        }
+        let is_negative = _mm256_cmpgt_epi64(borrow, top);
+        let mut result = [_mm256_setzero_si256(); 8];
+        for j in 0..8 {
+            result[j] = _mm256_or_si256(
+                _mm256_andnot_si256(is_negative, reduced[j]),
+                _mm256_and_si256(is_negative, limbs[j]),
+            );
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "simd")]
+    use super::Simd;
+    use super::{sum_of_products, Fp, WideFp, MODULUS};
+    use pairing::bn256::Fq;
+    use pairing::group::ff::{Field, PrimeField};
+    use rand_core::OsRng;
+
+    // q - 1 and elements close to the modulus hit the final subtraction
+    fn edge_elements() -> Vec<Fp> {
+        let mut minus_one = MODULUS;
+        minus_one[0] -= 1;
+        vec![
+            Fp::zero(),
+            Fp::one(),
+            -Fp::one(),
+            Fp::from_raw(minus_one),
+            Fp::from(2),
+            -Fp::from(2),
+            Fp([u64::MAX, u64::MAX, u64::MAX, MODULUS[3] - 1]),
+        ]
+    }
+
+    #[test]
+    fn test_matches_pairing_field() {
+        assert_eq!(Fp::one().to_raw(), [1, 0, 0, 0]);
+        assert_eq!(
+            (-Fp::one()).to_raw(),
+            [MODULUS[0] - 1, MODULUS[1], MODULUS[2], MODULUS[3]]
+        );
+
+        let mut elements = edge_elements()
+            .into_iter()
+            .map(Fq::from)
+            .collect::<Vec<_>>();
+        elements.extend((0..200).map(|_| Fq::random(OsRng)));
+        for a in elements.iter() {
+            let x = Fp::from(*a);
+            assert_eq!(Fq::from(x), *a);
+            assert_eq!(x.to_repr(), a.to_repr());
+            assert_eq!(Fp::from_repr(a.to_repr()).unwrap(), x);
+            assert_eq!(
+                Fq::from(x.invert().unwrap_or(Fp::zero())),
+                a.invert().unwrap_or(Fq::zero())
+            );
+            assert_eq!(x.square().sqrt().unwrap().square(), x.square());
+            for b in elements.iter().take(20) {
+                let y = Fp::from(*b);
+                assert_eq!(Fq::from(x + y), *a + b);
+                assert_eq!(Fq::from(x - y), *a - b);
+                assert_eq!(Fq::from(x * y), *a * b);
+                assert_eq!(
+                    x.cmp(&y),
+                    a.to_repr().iter().rev().cmp(b.to_repr().iter().rev())
+                );
+            }
+            assert_eq!(Fq::from(-x), -*a);
+        }
+        assert!(bool::from(Fp::from_repr([0xff; 32]).is_none()));
+        assert_eq!(Fp::root_of_unity().square(), Fp::one());
+        // -1 is not a square as q = 3 mod 4
+        assert!(bool::from((-Fp::one()).sqrt().is_none()));
+    }
+
+    #[test]
+    fn test_sum_of_products() {
+        let edges = edge_elements();
+        for a in edges.iter() {
+            for b in edges.iter() {
+                assert_eq!(sum_of_products(*a, *b, *b, *a), *a * *b + *b * *a);
+                assert_eq!(sum_of_products(*a, *a, *b, *b), a.square() + b.square());
+            }
+        }
+
+        (0..1000).for_each(|_| {
+            let [a0, b0, a1, b1] = [(); 4].map(|_| Fp::random(OsRng));
+            assert_eq!(sum_of_products(a0, b0, a1, b1).0, (a0 * b0 + a1 * b1).0);
+        });
+
+        // five maximal products stay in reduction bound
+        let max = -Fp::one();
+        let mut acc = WideFp::default();
+        (0..5).for_each(|_| acc.add_mul(max, max));
+        assert_eq!(acc.reduce(), max.square() * Fp::from(5));
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn test_simd_matches_scalar() {
+        #[cfg(target_arch = "x86_64")]
+        assert_eq!(Simd::detect().is_some(), is_x86_feature_detected!("avx2"));
+        let simd = match Simd::detect() {
+            Some(simd) => simd,
+            None => return,
+        };
+
+        // edge elements meet each other in every lane and the tail is left to the scalar path
+        let edges = edge_elements();
+        let mut elements = edges
+            .iter()
+            .flat_map(|a| edges.iter().map(move |b| (*a, *b)))
+            .flat_map(|(a, b)| [a, b])
+            .collect::<Vec<_>>();
+        elements.extend((0..1001).map(|_| Fp::random(OsRng)));
+        let n = elements.len() / 6;
+        let entries = elements.chunks(n).take(6).collect::<Vec<_>>();
+
+        let (mut left, mut right) = (entries[0].to_vec(), entries[1].to_vec());
+        simd.matrix_arithmetic(
+            &mut left,
+            &mut right,
+            [entries[2], entries[3], entries[4], entries[5]],
+        );
+        for i in 0..n {
+            let (l, r) = (entries[0][i], entries[1][i]);
+            assert_eq!(left[i].0, (entries[2][i] * l + entries[3][i] * r).0);
+            assert_eq!(right[i].0, (entries[4][i] * l + entries[5][i] * r).0);
+        }
+
+        // one matrix on every pair
+        let factor = [
+            entries[2][n - 1],
+            entries[3][n - 1],
+            entries[4][n - 1],
+            entries[5][n - 1],
+        ];
+        let (mut left, mut right) = (entries[0].to_vec(), entries[1].to_vec());
+        simd.uniform_matrix_arithmetic(&mut left, &mut right, factor);
+        for i in 0..n {
+            let (l, r) = (entries[0][i], entries[1][i]);
+            assert_eq!(left[i].0, (factor[0] * l + factor[1] * r).0);
+            assert_eq!(right[i].0, (factor[2] * l + factor[3] * r).0);
+        }
+    }
+}
@@ -1,5 +1,8 @@
-use pairing::arithmetic::BaseExt;
-use pairing::bn256::Fq as Fp;
+use super::arithmetic::pow_two_minus_one;
+use super::fftree::FactorMatrix;
+use super::field::Fp;
+use crate::utils::parallel_batch_inversion;
+
 use pairing::group::ff::Field;
 use rayon::prelude::*;
 
@@ -201,62 +204,124 @@ impl Isogeny {
         }
     }
 
-    pub(crate) fn evaluate(&self, x: Fp) -> Fp {
-        let Isogeny { a, b } = self;
-        let numerator = *b + *a * x + x.square();
-        let denominator = *a + x;
-        numerator * denominator.invert().unwrap()
-    }
-
     pub(crate) fn evaluate_with_denominator(&self, x: Fp) -> Fp {
         let Isogeny { a, b: _ } = self;
         *a + x
     }
 
+    // derivative of the isogeny u / v times v^2 which is u' v - u v'
+    pub(crate) fn evaluate_derivative_numerator(&self, x: Fp) -> Fp {
+        let Isogeny { a, b } = self;
+        (x + a).square() - b
+    }
+
+    // evaluate isogeny on the first half of domain inverting denominators at once
     pub(crate) fn domain_half_sizing(&self, domain: Vec<Fp>, size: usize) -> Vec<Fp> {
-        domain[..size]
+        self.images(&domain[..size])
+            .into_iter()
+            .collect::<Option<Vec<_>>>()
+            .expect("isogeny denominator vanishes on domain")
+    }
+
+    // isogeny images of domain points which are none at the poles
+    pub(crate) fn images(&self, domain: &[Fp]) -> Vec<Option<Fp>> {
+        let Isogeny { a, b } = self;
+        let mut denominators = domain
+            .par_iter()
+            .map(|coeff| self.evaluate_with_denominator(*coeff))
+            .collect::<Vec<_>>();
+        // zero denominators are left as they are
+        parallel_batch_inversion(&mut denominators);
+
+        domain
             .par_iter()
-            .map(|coeff| self.evaluate(*coeff))
+            .zip(denominators.par_iter())
+            .map(|(x, inv)| {
+                if *inv == Fp::zero() {
+                    None
+                } else {
+                    Some((*b + *a * x + x.square()) * inv)
+                }
+            })
             .collect()
     }
 
-    pub(crate) fn get_factor(
-        &self,
-        domain: &Vec<Fp>,
-        size: usize,
-        exp: &[u64; 4],
-    ) -> Vec<((Fp, Fp), (Fp, Fp))> {
+    pub(crate) fn get_factor(&self, domain: &[Fp], size: usize) -> FactorMatrix {
+        self.factor_matrices(domain, size).into()
+    }
+
+    fn factor_matrices(&self, domain: &[Fp], size: usize) -> Vec<((Fp, Fp), (Fp, Fp))> {
+        let log_size = size.trailing_zeros();
         domain[..size]
             .par_iter()
             .zip(&domain[size..])
             .map(|(a, b)| {
-                let f1 = self.evaluate_with_denominator(*a).pow(exp);
+                let f1 = pow_two_minus_one(self.evaluate_with_denominator(*a), log_size);
                 let f2 = a * f1;
-                let f3 = self.evaluate_with_denominator(*b).pow(exp);
+                let f3 = pow_two_minus_one(self.evaluate_with_denominator(*b), log_size);
                 let f4 = b * f3;
                 ((f1, f2), (f3, f4))
             })
             .collect()
     }
 
-    pub(crate) fn get_inv_factor(
-        &self,
-        domain: &Vec<Fp>,
-        size: usize,
-        exp: &[u64; 4],
-    ) -> Vec<((Fp, Fp), (Fp, Fp))> {
-        domain[..size]
+    // inverse of factor matrices on domain inverting determinants at once
+    pub(crate) fn get_inv_factor(&self, domain: &[Fp], size: usize) -> FactorMatrix {
+        let factor = self.factor_matrices(domain, size);
+        let mut divisors = factor
             .par_iter()
-            .zip(&domain[size..])
-            .map(|(a, b)| {
-                let f1 = self.evaluate_with_denominator(*a).pow(exp);
-                let f2 = a * f1;
-                let f3 = self.evaluate_with_denominator(*b).pow(exp);
-                let f4 = b * f3;
-                let denominator = f1 * f4 - f2 * f3;
-                let divisor = denominator.invert().unwrap();
-                ((f4 * divisor, -f2 * divisor), (-f3 * divisor, f1 * divisor))
+            .map(|((f1, f2), (f3, f4))| f1 * f4 - f2 * f3)
+            .collect::<Vec<_>>();
+        checked_batch_inversion(&mut divisors, "factor matrix is not invertible");
+
+        factor
+            .par_iter()
+            .zip(divisors.par_iter())
+            .map(|(((f1, f2), (f3, f4)), divisor)| {
+                (
+                    (f4 * divisor, -*f2 * divisor),
+                    (-*f3 * divisor, f1 * divisor),
+                )
             })
-            .collect()
+            .collect::<Vec<_>>()
+            .into()
+    }
+}
+
+// batch inversion which panics on zero as inverting one by one does
+fn checked_batch_inversion(values: &mut [Fp], message: &str) {
+    assert!(
+        values.par_iter().all(|value| *value != Fp::zero()),
+        "{}",
+        message
+    );
+    parallel_batch_inversion(values);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Fp, Isogeny};
+    use pairing::group::ff::Field;
+    use rand_core::OsRng;
+
+    #[test]
+    #[should_panic(expected = "isogeny denominator vanishes on domain")]
+    fn test_zero_denominator() {
+        let isogeny = Isogeny::new(1);
+        let domain = vec![
+            Fp::random(OsRng),
+            -isogeny.a,
+            Fp::random(OsRng),
+            Fp::random(OsRng),
+        ];
+        isogeny.domain_half_sizing(domain, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "factor matrix is not invertible")]
+    fn test_singular_factor() {
+        let isogeny = Isogeny::new(1);
+        let x = Fp::random(OsRng);
+        isogeny.get_inv_factor(&[Fp::random(OsRng), x, Fp::random(OsRng), x], 2);
     }
 }
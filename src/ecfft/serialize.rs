@@ -0,0 +1,347 @@
+// binary layout of precomputed ecfft params
+// integers are little-endian and field elements are canonical 32 bytes
+// magic `ECFFTTBL`, version u32, curve digest of 32 bytes, max_k u32, caches and blake2b checksum
+// each cache is k, coset, powered_coset and trees of domain, factor and inv_factor
+// vectors are prefixed by u64 length and factor matrices by u32 layout tag
+
+use super::fftree::{FactorMatrix, FfTree};
+use super::utils::EcFftCache;
+use super::Ep;
+
+use pairing::bn256::Fq;
+use pairing::group::ff::PrimeField;
+use std::io::{self, Read, Write};
+
+const MAGIC: &[u8; 8] = b"ECFFTTBL";
+const VERSION: u32 = 2;
+const PERSONALIZATION: &[u8; 16] = b"ecfft_params_v01";
+const CHECKSUM_SIZE: usize = 64;
+const ELEMENT_SIZE: usize = 32;
+
+const FULL_LAYOUT: u32 = 0;
+const COMPACT_LAYOUT: u32 = 1;
+
+// write caches with header and checksum
+pub(crate) fn write_caches<'a, W: Write>(
+    mut writer: W,
+    max_k: usize,
+    caches: impl IntoIterator<Item = &'a EcFftCache>,
+) -> io::Result<()> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&VERSION.to_le_bytes());
+    bytes.extend_from_slice(&params_digest(max_k));
+    bytes.extend_from_slice(&(max_k as u32).to_le_bytes());
+
+    caches.into_iter().for_each(|cache| {
+        bytes.extend_from_slice(&(cache.k as u32).to_le_bytes());
+        write_elements(&mut bytes, &cache.coset);
+        write_elements(&mut bytes, &cache.powered_coset);
+        bytes.extend_from_slice(&(cache.trees.len() as u32).to_le_bytes());
+        cache.trees.iter().for_each(|tree| {
+            let (s, s_prime) = &tree.domain;
+            write_elements(&mut bytes, s);
+            write_elements(&mut bytes, s_prime);
+            write_factors(&mut bytes, &tree.factor);
+            write_factors(&mut bytes, &tree.inv_factor);
+        });
+    });
+
+    let checksum = checksum(&bytes);
+    writer.write_all(&bytes)?;
+    writer.write_all(&checksum)
+}
+
+// read caches checking checksum, version, params and every length
+pub(crate) fn read_caches<R: Read>(mut reader: R) -> io::Result<(usize, Vec<EcFftCache>)> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+
+    if bytes.len() < CHECKSUM_SIZE {
+        return Err(invalid_data("ecfft params are truncated"));
+    }
+    let (body, expected) = bytes.split_at(bytes.len() - CHECKSUM_SIZE);
+    if checksum(body)[..] != expected[..] {
+        return Err(invalid_data("ecfft params checksum mismatch"));
+    }
+
+    let mut decoder = Decoder { bytes: body };
+    if decoder.take(MAGIC.len())? != MAGIC {
+        return Err(invalid_data("ecfft params magic mismatch"));
+    }
+    let version = decoder.read_u32()?;
+    if version != VERSION {
+        return Err(invalid_data(&format!(
+            "unsupported ecfft params version {}, expected {}",
+            version, VERSION
+        )));
+    }
+    let params = decoder.take(32)?.to_vec();
+    let max_k = decoder.read_u32()? as usize;
+    if max_k == 0 || max_k > 14 || params[..] != params_digest(max_k)[..] {
+        return Err(invalid_data(
+            "ecfft params were generated for another curve",
+        ));
+    }
+
+    let caches = (0..max_k)
+        .map(|i| decoder.read_cache(max_k - i))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    if !decoder.bytes.is_empty() {
+        return Err(invalid_data("ecfft params have trailing bytes"));
+    }
+
+    Ok((max_k, caches))
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, size: usize) -> io::Result<&'a [u8]> {
+        if self.bytes.len() < size {
+            return Err(invalid_data("ecfft params are truncated"));
+        }
+        let (head, tail) = self.bytes.split_at(size);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn read_u32(&mut self) -> io::Result<u32> {
+        let mut buf = [0u8; 4];
+        buf.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(buf))
+    }
+
+    fn read_u64(&mut self) -> io::Result<u64> {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    fn read_len(&mut self, expected: usize) -> io::Result<()> {
+        let len = self.read_u64()?;
+        if len != expected as u64 {
+            return Err(invalid_data(&format!(
+                "ecfft params length mismatch {}, expected {}",
+                len, expected
+            )));
+        }
+        Ok(())
+    }
+
+    fn read_element<F: PrimeField>(&mut self) -> io::Result<F> {
+        let mut repr = F::Repr::default();
+        repr.as_mut().copy_from_slice(self.take(ELEMENT_SIZE)?);
+        Option::from(F::from_repr(repr))
+            .ok_or_else(|| invalid_data("ecfft params contain non canonical element"))
+    }
+
+    fn read_elements<F: PrimeField>(&mut self, expected: usize) -> io::Result<Vec<F>> {
+        self.read_len(expected)?;
+        (0..expected).map(|_| self.read_element()).collect()
+    }
+
+    fn read_factors(&mut self, expected: usize) -> io::Result<FactorMatrix> {
+        match self.read_u32()? {
+            FULL_LAYOUT => Ok(FactorMatrix::Full([
+                self.read_elements(expected)?.into(),
+                self.read_elements(expected)?.into(),
+                self.read_elements(expected)?.into(),
+                self.read_elements(expected)?.into(),
+            ])),
+            COMPACT_LAYOUT => Ok(FactorMatrix::Compact([
+                self.read_elements(expected)?.into(),
+                self.read_elements(expected)?.into(),
+            ])),
+            _ => Err(invalid_data("ecfft params have unknown factor layout")),
+        }
+    }
+
+    fn read_cache(&mut self, expected_k: usize) -> io::Result<EcFftCache> {
+        let k = self.read_u32()? as usize;
+        if k != expected_k {
+            return Err(invalid_data("ecfft params cache order mismatch"));
+        }
+        let n = 1 << k;
+        let coset = self.read_elements::<Fq>(n)?;
+        let powered_coset = self.read_elements(n)?;
+        if self.read_u32()? as usize != k {
+            return Err(invalid_data("ecfft params tree depth mismatch"));
+        }
+
+        let trees = (0..k)
+            .map(|depth| {
+                let half_n = 1 << (k - depth - 1);
+                let factor_n = if depth == k - 1 { 0 } else { half_n >> 1 };
+                let s = self.read_elements(half_n)?;
+                let s_prime = self.read_elements(half_n)?;
+                let factor = self.read_factors(factor_n)?;
+                let inv_factor = self.read_factors(factor_n)?;
+                Ok(FfTree {
+                    domain: (s.into(), s_prime.into()),
+                    factor,
+                    inv_factor,
+                })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        Ok(EcFftCache {
+            k,
+            trees: trees.into(),
+            coset: coset.into(),
+            powered_coset: powered_coset.into(),
+        })
+    }
+}
+
+fn write_elements<F: PrimeField>(bytes: &mut Vec<u8>, elements: &[F]) {
+    bytes.extend_from_slice(&(elements.len() as u64).to_le_bytes());
+    elements
+        .iter()
+        .for_each(|element| bytes.extend_from_slice(element.to_repr().as_ref()));
+}
+
+fn write_factors(bytes: &mut Vec<u8>, factors: &FactorMatrix) {
+    match factors {
+        FactorMatrix::Full(entries) => {
+            bytes.extend_from_slice(&FULL_LAYOUT.to_le_bytes());
+            entries
+                .iter()
+                .for_each(|entry| write_elements(bytes, entry));
+        }
+        FactorMatrix::Compact(scalars) => {
+            bytes.extend_from_slice(&COMPACT_LAYOUT.to_le_bytes());
+            scalars
+                .iter()
+                .for_each(|scalar| write_elements(bytes, scalar));
+        }
+    }
+}
+
+// digest of the curve params the tables are derived from
+fn params_digest(max_k: usize) -> [u8; 32] {
+    let mut state = blake2b_simd::Params::new()
+        .hash_length(32)
+        .personal(PERSONALIZATION)
+        .to_state();
+    state.update(&(max_k as u32).to_le_bytes());
+    state.update(
+        Ep::generator()
+            .to_affine()
+            .point_projective()
+            .to_repr()
+            .as_ref(),
+    );
+    state.update(
+        Ep::representative()
+            .to_affine()
+            .point_projective()
+            .to_repr()
+            .as_ref(),
+    );
+    let mut digest = [0u8; 32];
+    digest.copy_from_slice(state.finalize().as_bytes());
+    digest
+}
+
+fn checksum(bytes: &[u8]) -> [u8; CHECKSUM_SIZE] {
+    let hash = blake2b_simd::Params::new()
+        .hash_length(CHECKSUM_SIZE)
+        .personal(PERSONALIZATION)
+        .hash(bytes);
+    let mut checksum = [0u8; CHECKSUM_SIZE];
+    checksum.copy_from_slice(hash.as_bytes());
+    checksum
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{checksum, read_caches, write_caches, CHECKSUM_SIZE};
+    use crate::ecfft::utils::EcFftCache;
+    use crate::test::{arb_poly_fq, layer_coset};
+    use crate::EcFft;
+
+    fn small_caches(max_k: usize) -> Vec<EcFftCache> {
+        (0..max_k)
+            .map(|i| EcFftCache::new(max_k - i, layer_coset(14 - max_k + i)))
+            .collect()
+    }
+
+    fn reseal(bytes: &mut [u8]) {
+        let body_len = bytes.len() - CHECKSUM_SIZE;
+        let checksum = checksum(&bytes[..body_len]);
+        bytes[body_len..].copy_from_slice(&checksum);
+    }
+
+    #[test]
+    fn test_ecfft_write_and_read() {
+        let ecfft = EcFft::new();
+        let mut bytes = Vec::new();
+        ecfft.write_to(&mut bytes).unwrap();
+        let restored = EcFft::read_from(&bytes[..]).unwrap();
+
+        for k in 1..14 {
+            let poly = arb_poly_fq(k);
+            assert_eq!(ecfft.evaluate(k, poly.clone()), restored.evaluate(k, poly));
+            assert_eq!(ecfft.get_cache(k), restored.get_cache(k));
+        }
+    }
+
+    #[test]
+    fn test_reject_corrupted_params() {
+        let max_k = 5;
+        let caches = small_caches(max_k);
+        let mut bytes = Vec::new();
+        write_caches(&mut bytes, max_k, &caches).unwrap();
+
+        let (restored_k, restored) = read_caches(&bytes[..]).unwrap();
+        assert_eq!(restored_k, max_k);
+        assert_eq!(restored, caches);
+
+        // compact layout
+        let compact = caches
+            .iter()
+            .cloned()
+            .map(EcFftCache::compact)
+            .collect::<Vec<_>>();
+        let mut compact_bytes = Vec::new();
+        write_caches(&mut compact_bytes, max_k, &compact).unwrap();
+        assert!(compact_bytes.len() < bytes.len());
+        assert_eq!(read_caches(&compact_bytes[..]).unwrap().1, compact);
+
+        // flipped bit
+        let mut corrupted = bytes.clone();
+        corrupted[100] ^= 1;
+        assert!(read_caches(&corrupted[..]).is_err());
+
+        // truncated
+        assert!(read_caches(&bytes[..bytes.len() - 1]).is_err());
+        assert!(read_caches(&bytes[..10]).is_err());
+
+        // unknown version with valid checksum
+        let mut versioned = bytes.clone();
+        versioned[8] = 1;
+        reseal(&mut versioned);
+        assert!(read_caches(&versioned[..]).is_err());
+
+        // max_k doesn't match the params digest
+        let mut mismatched = bytes.clone();
+        mismatched[44] = 6;
+        reseal(&mut mismatched);
+        assert!(read_caches(&mismatched[..]).is_err());
+
+        // non canonical element with valid checksum
+        let mut non_canonical = bytes;
+        let first_element = 48 + 4 + 8;
+        non_canonical[first_element..first_element + 32].copy_from_slice(&[0xff; 32]);
+        reseal(&mut non_canonical);
+        assert!(read_caches(&non_canonical[..]).is_err());
+    }
+}
@@ -1,22 +1,83 @@
-use super::arithmetic::{parallel_low_degree_extention, serial_low_degree_extention};
-use super::fftree::FfTree;
+use super::arithmetic::{
+    parallel_low_degree_extention, pow_small, serial_low_degree_extention, square_times,
+};
+use super::fftree::{Extension, FactorMatrix, FactorView, FfTree, Table};
+use super::field::Fp;
 use super::isogeny::Isogeny;
+use super::Ep;
+use crate::polynomial::SharedPoints;
+use crate::utils::parallel_batch_inversion;
 
-use pairing::{arithmetic::BaseExt, bn256::Fq as Fp};
-use rayon::join;
+use once_cell::sync::OnceCell;
+use pairing::bn256::Fq;
+use rayon::{current_num_threads, join, prelude::*};
+use std::borrow::Cow;
+use std::mem::size_of;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub(crate) struct EcFftCache {
     pub(crate) k: usize,
-    pub(crate) trees: Vec<FfTree>,
-    pub(crate) coset: Vec<Fp>,
-    pub(crate) powered_coset: Vec<Fp>,
+    pub(crate) trees: Cow<'static, [FfTree]>,
+    // points of the domain in the public field
+    pub(crate) coset: SharedPoints<Fq>,
+    pub(crate) powered_coset: Table,
+}
+
+// data of a degree derived from its params on first use
+// it is held apart from the params so that embedded ones are never written
+#[derive(Debug, Default)]
+pub(crate) struct Derived {
+    // barycentric weights of the coset in the public field of the evaluations they weigh
+    pub(crate) barycentric_weights: OnceCell<Vec<Fq>>,
+    // params of interpolation from the coset
+    pub(crate) exit_params: OnceCell<ExitParams>,
+}
+
+// exit reduces by z = x^{n/2} + r which vanishes on s in the way of montgomery reduction
+// r has n/2 degree and agrees with -x^{n/2} on s
+#[derive(Clone, Debug)]
+pub(crate) struct ExitParams {
+    // compact factors of every layer extending from s_prime to s
+    pub(crate) reverse: Vec<(FactorMatrix, FactorMatrix)>,
+    // 1 / x^{n/2} on s
+    pub(crate) inv_powered: Vec<Fp>,
+    // 1 / z on s_prime
+    pub(crate) inv_vanishing: Vec<Fp>,
+    // z^2 mod x^{n/2} on s and s_prime
+    pub(crate) squared: (Vec<Fp>, Vec<Fp>),
+}
+
+impl ExitParams {
+    // heap size of params in bytes
+    pub(crate) fn memory_size(&self) -> usize {
+        let (squared, squared_prime) = &self.squared;
+        let values =
+            self.inv_powered.len() + self.inv_vanishing.len() + squared.len() + squared_prime.len();
+        let reverse = self
+            .reverse
+            .iter()
+            .map(|(factor, inv_factor)| factor.memory_size() + inv_factor.memory_size())
+            .sum::<usize>();
+        values * size_of::<Fp>() + reverse
+    }
+}
+
+impl Derived {
+    // empty data of every degree from 2^max_k down to 2
+    pub(crate) fn levels(max_k: usize) -> Vec<Derived> {
+        (0..max_k).map(|_| Derived::default()).collect()
+    }
+
+    // heap size of derived data in bytes
+    pub(crate) fn memory_size(&self) -> usize {
+        let weights = self.barycentric_weights.get().map_or(0, Vec::len) * size_of::<Fq>();
+        weights + self.exit_params.get().map_or(0, ExitParams::memory_size)
+    }
 }
 
 impl EcFftCache {
     pub fn new(k: usize, coset: Vec<Fp>) -> Self {
         let max_k = 14;
-        let n = 1 << k;
 
         assert!(k <= max_k);
         assert_eq!(coset.len(), 1 << k);
@@ -33,23 +94,22 @@ impl EcFftCache {
             .for_each(|((a, b), c)| {
                 *b = a[0];
                 *c = a[1];
-                powered_coset.push(a[0].pow(&[n >> 1, 0, 0, 0]));
-                powered_coset.push(a[1].pow(&[n >> 1, 0, 0, 0]));
+                powered_coset.push(square_times(a[0], k - 1));
+                powered_coset.push(square_times(a[1], k - 1));
             });
 
         for i in 1..k {
             let isogeny = Isogeny::new(i);
             let n = 1 << (k - i);
             let half_n = n >> 1;
-            let exp = &[(half_n - 1) as u64, 0, 0, 0];
 
             let (inv_factor, factor) = join(
-                || isogeny.get_inv_factor(&s, half_n, exp),
-                || isogeny.get_factor(&s_prime, half_n, exp),
+                || isogeny.get_inv_factor(&s, half_n),
+                || isogeny.get_factor(&s_prime, half_n),
             );
 
             trees.push(FfTree {
-                domain: (s.clone(), s_prime.clone()),
+                domain: (s.clone().into(), s_prime.clone().into()),
                 factor,
                 inv_factor,
             });
@@ -66,24 +126,183 @@ impl EcFftCache {
 
         EcFftCache {
             k,
-            trees,
-            coset,
-            powered_coset,
+            trees: trees.into(),
+            coset: coset.into_iter().map(Fq::from).collect::<Vec<_>>().into(),
+            powered_coset: powered_coset.into(),
         }
     }
 
-    pub(crate) fn get_tree(&self, depth: usize) -> &FfTree {
-        &self.trees[depth]
+    // derive half degree params from this one
+    // the coset and each layer domain of half degree params are respectively the even and odd
+    // points of this one, so only the factors need to be computed
+    // factors are powers v^{n/2 - 1} of the isogeny denominator whose ones of this degree are
+    // v^{n - 1}, so they can't be taken from this one without square roots and are computed
+    // again, which saves about a third of building the params from scratch (22ms against 34ms
+    // at k = 13 in the precompute benches) as the coset and domains are not generated or mapped
+    pub(crate) fn half_sizing(&self) -> Self {
+        assert!(self.k > 1);
+        let k = self.k - 1;
+
+        let coset = self.coset.iter().step_by(2).copied().collect::<Vec<_>>();
+        let powered_coset = coset
+            .par_iter()
+            .map(|point| square_times(Fp::from(*point), k - 1))
+            .collect::<Vec<_>>();
+
+        let trees = self.trees[..k]
+            .par_iter()
+            .enumerate()
+            .map(|(depth, tree)| {
+                let (domain, _) = &tree.domain;
+                let s = domain.iter().step_by(2).copied().collect::<Vec<_>>();
+                let s_prime = domain
+                    .iter()
+                    .skip(1)
+                    .step_by(2)
+                    .copied()
+                    .collect::<Vec<_>>();
+
+                if depth == k - 1 {
+                    return FfTree::last_tree(s, s_prime);
+                }
+
+                let isogeny = Isogeny::new(depth + 1);
+                let half_n = 1 << (k - depth - 2);
+                let (inv_factor, factor) = join(
+                    || isogeny.get_inv_factor(&s, half_n),
+                    || isogeny.get_factor(&s_prime, half_n),
+                );
+
+                FfTree {
+                    domain: (s.into(), s_prime.into()),
+                    factor,
+                    inv_factor,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        EcFftCache {
+            k,
+            trees: trees.into(),
+            coset: coset.into(),
+            powered_coset: powered_coset.into(),
+        }
     }
 
-    pub(crate) fn get_last_tree(&self) -> (&((Fp, Fp), (Fp, Fp)), &((Fp, Fp), (Fp, Fp))) {
-        let (factor, inv_factor) = self.trees[self.trees.len() - 2].get_factors();
-        (&factor[0], &inv_factor[0])
+    // factors of every layer extending from s_prime to s which are m(s) onto s and m(s_prime)^-1
+    // from s_prime, derived from the ones of this direction by one batch inversion
+    // m = [[p, a p], [q, b q]] on (a, b) is kept compact as p and q, and its inverse as
+    // u = 1 / p(b - a) and w = 1 / q(b - a), so each one is the inverse of the other's scalar
+    pub(crate) fn reverse_factors(&self) -> Vec<(FactorMatrix, FactorMatrix)> {
+        let layers = &self.trees[..self.k - 1];
+        let mut inverses = layers
+            .iter()
+            .flat_map(|tree| {
+                let (s, s_prime) = &tree.domain;
+                let (factor, inv_factor) = tree.get_factors();
+                let half_n = factor.len();
+                (0..half_n).flat_map(move |i| {
+                    let ((p, _), (q, _)) = factor.entry(i);
+                    let (_, (minus_u, w)) = inv_factor.entry(i);
+                    let diff = s[i + half_n] - s[i];
+                    let diff_prime = s_prime[i + half_n] - s_prime[i];
+                    [-(minus_u * diff), w * diff, p * diff_prime, q * diff_prime]
+                })
+            })
+            .collect::<Vec<_>>();
+        parallel_batch_inversion(&mut inverses);
+
+        let mut offset = 0;
+        layers
+            .iter()
+            .map(|tree| {
+                let half_n = tree.domain.0.len() >> 1;
+                let chunk = &inverses[offset * 4..(offset + half_n) * 4];
+                offset += half_n;
+                let entry = |j: usize| chunk.iter().skip(j).step_by(4).copied().collect::<Vec<_>>();
+                (
+                    FactorMatrix::Compact([entry(0).into(), entry(1).into()]),
+                    FactorMatrix::Compact([entry(2).into(), entry(3).into()]),
+                )
+            })
+            .collect()
     }
 
-    #[cfg(test)]
-    pub(crate) fn get_coset(&self) -> &Vec<Fp> {
-        &self.coset
+    // extension from s_prime to s by the reverse factors of this params
+    pub(crate) fn reverse<'a>(
+        &'a self,
+        factors: &'a [(FactorMatrix, FactorMatrix)],
+    ) -> ReverseExtension<'a> {
+        ReverseExtension {
+            cache: self,
+            factors,
+        }
+    }
+
+    // keep two scalars of each factor matrix deriving the others from the domain
+    pub(crate) fn compact(self) -> Self {
+        EcFftCache {
+            trees: self
+                .trees
+                .into_owned()
+                .into_iter()
+                .map(FfTree::compact)
+                .collect::<Vec<_>>()
+                .into(),
+            ..self
+        }
+    }
+
+    // heap size of precomputed params in bytes
+    pub(crate) fn memory_size(&self) -> usize {
+        let trees = self
+            .trees
+            .iter()
+            .map(|tree| tree.memory_size())
+            .sum::<usize>();
+        self.coset.len() * size_of::<Fq>() + self.powered_coset.len() * size_of::<Fp>() + trees
+    }
+
+    // w_i = 1 / z'(x_i) for z which vanishes on the coset = s + s_prime
+    // the isogeny psi = u / v of a layer maps pairs of s of n size to the next s, so that
+    // z_s = v^{n/2} z_{psi(s)}(psi) and likewise for s_prime, and then for x on s
+    //   z_s'(x) z_{s_prime}(x)
+    //     = v(x)^{n-2} (u' v - u v')(x) z_{psi(s)}'(psi(x)) z_{psi(s_prime)}(psi(x))
+    // which is walked down the layers to single points in n log^2 n multiplications
+    pub(crate) fn barycentric_weights(&self) -> Vec<Fq> {
+        let k = self.k;
+        let isogenies = (1..k).map(Isogeny::new).collect::<Vec<_>>();
+        let (s, s_prime) = &self.trees[k - 1].domain;
+        let last = s[0] - s_prime[0];
+
+        let mut weights = (0..1 << k)
+            .into_par_iter()
+            .map(|i| {
+                let is_even = i & 1 == 0;
+                let weight = self.trees[..k - 1].iter().zip(isogenies.iter()).fold(
+                    Fp::one(),
+                    |acc, (tree, isogeny)| {
+                        let (s, s_prime) = &tree.domain;
+                        let domain = if is_even { s } else { s_prime };
+                        let x = domain[(i >> 1) & (domain.len() - 1)];
+                        let v = isogeny.evaluate_with_denominator(x);
+                        acc * pow_small(v, domain.len() - 2)
+                            * isogeny.evaluate_derivative_numerator(x)
+                    },
+                );
+                if is_even {
+                    weight * last
+                } else {
+                    -weight * last
+                }
+            })
+            .collect::<Vec<_>>();
+        parallel_batch_inversion(&mut weights);
+        weights.into_par_iter().map(Fq::from).collect()
+    }
+
+    pub(crate) fn get_tree(&self, depth: usize) -> &FfTree {
+        &self.trees[depth]
     }
 
     // evaluate n/2 size of polynomial on n size coset
@@ -97,20 +316,95 @@ impl EcFftCache {
     }
 
     // evaluate n/2 size of polynomial on n size coset
-    pub(crate) fn par_extend(&self, poly: &mut [Fp], poly_prime: &mut [Fp], k: usize) {
+    pub(crate) fn par_extend(
+        &self,
+        poly: &mut [Fp],
+        poly_prime: &mut [Fp],
+        k: usize,
+        cutoff: usize,
+    ) {
         if k == 1 {
             return;
         }
 
         let n = 1 << (self.k - 1);
-        parallel_low_degree_extention(poly, poly_prime, n, k, 0, &self);
+        parallel_low_degree_extention(poly, poly_prime, n, k, 0, cutoff, &self);
     }
 }
 
+impl Extension for EcFftCache {
+    fn get_factors(&self, depth: usize) -> (FactorView<'_>, FactorView<'_>) {
+        self.trees[depth].get_factors()
+    }
+
+    fn get_last_tree(&self) -> (((Fp, Fp), (Fp, Fp)), ((Fp, Fp), (Fp, Fp))) {
+        let (factor, inv_factor) = self.trees[self.trees.len() - 2].get_factors();
+        (factor.entry(0), inv_factor.entry(0))
+    }
+}
+
+// params extending from s_prime to s on the layer domains of the forward ones
+pub(crate) struct ReverseExtension<'a> {
+    cache: &'a EcFftCache,
+    factors: &'a [(FactorMatrix, FactorMatrix)],
+}
+
+impl Extension for ReverseExtension<'_> {
+    fn get_factors(&self, depth: usize) -> (FactorView<'_>, FactorView<'_>) {
+        let (s, s_prime) = &self.cache.trees[depth].domain;
+        let (factor, inv_factor) = &self.factors[depth];
+        (factor.view(s, false), inv_factor.view(s_prime, true))
+    }
+
+    fn get_last_tree(&self) -> (((Fp, Fp), (Fp, Fp)), ((Fp, Fp), (Fp, Fp))) {
+        let (factor, inv_factor) = self.get_factors(self.factors.len() - 1);
+        (factor.entry(0), inv_factor.entry(0))
+    }
+}
+
+// params of every degree from 2^max_k down to 2
+pub(crate) fn precompute_caches(max_k: usize) -> Vec<EcFftCache> {
+    let mut caches = vec![EcFftCache::new(max_k, generate_coset(max_k, 0))];
+    for i in 1..max_k {
+        let cache = caches[i - 1].half_sizing();
+        caches.push(cache);
+    }
+    caches
+}
+
+// coset of 2^{max_k - depth} size subgroup
+// each thread adds the generator incrementally from its first point and normalises them at once
+pub(crate) fn generate_coset(max_k: usize, depth: usize) -> Vec<Fp> {
+    let n = 1 << (max_k - depth);
+    let chunk_size = (n / current_num_threads()).max(1);
+    let step = (Ep::generator() * Fp::from_raw([1 << depth, 0, 0, 0])).to_affine();
+    let mut coset = vec![Fp::zero(); n];
+
+    coset
+        .par_chunks_mut(chunk_size)
+        .enumerate()
+        .for_each(|(i, chunk)| {
+            let offset = ((i * chunk_size) << depth) as u64;
+            let mut acc = Ep::representative() + Ep::generator() * Fp::from_raw([offset, 0, 0, 0]);
+            let points = (0..chunk.len())
+                .map(|_| {
+                    let point = acc;
+                    acc = &acc + &step;
+                    point
+                })
+                .collect::<Vec<_>>();
+            chunk.copy_from_slice(&Ep::batch_point_projective(&points));
+        });
+
+    coset
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{EcFftCache, Isogeny};
-    use crate::test::{arb_poly_fq, layer_coset};
+    use super::{generate_coset, EcFftCache};
+    use crate::ecfft::arithmetic::parallel_single_low_degree_extention;
+    use crate::ecfft::Fp;
+    use crate::test::{arb_poly, layer_coset, naive_layer_coset};
     use proptest::prelude::*;
 
     #[test]
@@ -119,61 +413,55 @@ mod tests {
 
         for d in 0..max_k {
             let k = max_k - d;
-            let coset = layer_coset(d);
-            let ecfft_params = EcFftCache::new(k, coset);
-            let cache = ecfft_params.get_tree(0);
-            let (mut s, mut s_prime) = cache.domain.clone();
+            let ecfft_params = EcFftCache::new(k, layer_coset(d));
+            let violations = ecfft_params.verify();
+            assert!(violations.is_empty(), "{:?}", violations);
+        }
+    }
 
-            for i in 0..(k - 1) {
-                let n = 1 << (k - (i + 1));
-                let half_n = n / 2;
-                let isogeny = Isogeny::new(i + 1);
+    #[test]
+    fn test_generate_coset() {
+        for depth in [0, 3, 8, 13] {
+            assert_eq!(generate_coset(14, depth), naive_layer_coset(depth));
+        }
+    }
 
-                s = s.iter().map(|coeff| isogeny.evaluate(*coeff)).collect();
-                s_prime = s_prime
-                    .iter()
-                    .map(|coeff| isogeny.evaluate(*coeff))
-                    .collect();
-                s.sort();
-                s.dedup();
-                s_prime.sort();
-                s_prime.dedup();
-
-                assert_eq!(half_n, s.len());
-                assert_eq!(half_n, s_prime.len());
-
-                let cache = ecfft_params.get_tree(i + 1);
-                let (mut l, mut l_prime) = cache.domain.clone();
-                l.sort();
-                l_prime.sort();
-
-                assert_eq!(s, l);
-                assert_eq!(s_prime, l_prime);
-
-                let (s, s_prime) = cache.domain.clone();
-                s[..half_n]
-                    .iter()
-                    .zip(&s[half_n..])
-                    .zip(&s_prime[..half_n])
-                    .zip(&s_prime[half_n..])
-                    .for_each(|(((a, b), c), d)| {
-                        assert_eq!(isogeny.evaluate(*a), isogeny.evaluate(*b));
-                        assert_eq!(isogeny.evaluate(*c), isogeny.evaluate(*d));
-                    });
-            }
+    #[test]
+    fn test_half_sizing() {
+        let max_k = 14;
+        let mut ecfft_params = EcFftCache::new(max_k, layer_coset(0));
+
+        for d in 1..max_k {
+            ecfft_params = ecfft_params.half_sizing();
+            assert_eq!(ecfft_params, EcFftCache::new(max_k - d, layer_coset(d)));
+        }
+    }
 
-            assert_eq!(s.len(), 1);
-            assert_eq!(s_prime.len(), 1);
+    #[test]
+    fn test_reverse_extension() {
+        for k in [2, 3, 8] {
+            let params = EcFftCache::new(k, layer_coset(14 - k));
+            let factors = params.reverse_factors();
+            let (s, s_prime) = params.get_tree(0).domain.clone();
+            let poly = arb_poly::<Fp>(1 << (k - 1));
+
+            // serial and split down to the bottom
+            for cutoff in [14, 1] {
+                let mut evals = poly.to_point_value(&s_prime).values;
+                let reverse = params.reverse(&factors);
+                parallel_single_low_degree_extention(&mut evals, k, 0, cutoff, &reverse);
+                assert_eq!(evals, poly.to_point_value(&s).values);
+            }
         }
     }
 
     proptest! {
         #![proptest_config(ProptestConfig::with_cases(100))]
         #[test]
-        fn test_extend_operation(k in 1usize..10) {
+        fn test_extend_operation(k in 1usize..10, cutoff in 0usize..8) {
             let depth = 14 - k;
-            let poly_a = arb_poly_fq(k - 1);
-            let poly_b = arb_poly_fq(k - 1);
+            let poly_a = arb_poly::<Fp>(1 << (k - 1));
+            let poly_b = arb_poly::<Fp>(1 << (k - 1));
             let coset = layer_coset(depth);
             let ecfft_params = EcFftCache::new(k, coset);
             let cache = ecfft_params.get_tree(0);
@@ -186,12 +474,13 @@ mod tests {
             let mut par_evals_s_alt = evals_s_alt.clone();
 
             ecfft_params.extend(&mut evals_s.values, &mut evals_s_alt.values, k);
-            ecfft_params.par_extend(&mut par_evals_s.values, &mut par_evals_s_alt.values, k);
+            ecfft_params.par_extend(&mut par_evals_s.values, &mut par_evals_s_alt.values, k, cutoff);
 
-            assert_eq!(evals_s, evals_s_prime);
-            assert_eq!(evals_s_alt, evals_s_prime_alt);
-            assert_eq!(par_evals_s, evals_s_prime);
-            assert_eq!(par_evals_s_alt, evals_s_prime_alt);
+            // extended values are on s_prime while they are still labelled with s
+            assert_eq!(evals_s.values, evals_s_prime.values);
+            assert_eq!(evals_s_alt.values, evals_s_prime_alt.values);
+            assert_eq!(par_evals_s.values, evals_s_prime.values);
+            assert_eq!(par_evals_s_alt.values, evals_s_prime_alt.values);
         }
     }
 }
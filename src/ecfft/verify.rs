@@ -0,0 +1,258 @@
+use super::arithmetic::square_times;
+use super::field::Fp;
+use super::isogeny::Isogeny;
+use super::utils::EcFftCache;
+
+/// Invariant of precomputed params which doesn't hold
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Violation {
+    /// coset, powered coset, trees, domains or factors have unexpected length
+    Size { k: usize, depth: Option<usize> },
+    /// powered coset is not `coset^(n/2)`
+    PoweredCoset { k: usize, index: usize },
+    /// domain contains the same point twice
+    DuplicatedDomain { k: usize, depth: Option<usize> },
+    /// first layer domain is not the even and odd points of coset
+    CosetDomain { k: usize },
+    /// isogeny maps `s[index]` and `s[index + half]` to different points
+    IsogenyPair {
+        k: usize,
+        depth: usize,
+        index: usize,
+    },
+    /// isogeny denominator vanishes on `s[index]` or `s_prime[index]`
+    IsogenyPole {
+        k: usize,
+        depth: usize,
+        index: usize,
+    },
+    /// next layer domain is not the isogeny image of this layer
+    IsogenyImage { k: usize, depth: usize },
+    /// factor doesn't match the isogeny on `s_prime`
+    Factor {
+        k: usize,
+        depth: usize,
+        index: usize,
+    },
+    /// inverse factor times the isogeny factor on `s` is not the identity
+    InvFactor {
+        k: usize,
+        depth: usize,
+        index: usize,
+    },
+}
+
+/// Audit result of precomputed params
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// polynomial degrees 2^k whose params were checked
+    pub checked: Vec<usize>,
+    /// violated invariants
+    pub violations: Vec<Violation>,
+}
+
+impl VerifyReport {
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl EcFftCache {
+    // check invariants of params and return violated ones
+    pub(crate) fn verify(&self) -> Vec<Violation> {
+        let k = self.k;
+        let n = 1 << k;
+
+        if self.coset.len() != n || self.powered_coset.len() != n || self.trees.len() != k {
+            return vec![Violation::Size { k, depth: None }];
+        }
+
+        let mut violations = Vec::new();
+        let coset = self.coset.iter().map(|x| Fp::from(*x)).collect::<Vec<_>>();
+
+        coset
+            .iter()
+            .zip(self.powered_coset.iter())
+            .enumerate()
+            .filter(|(_, (point, powered))| square_times(**point, k - 1) != **powered)
+            .for_each(|(index, _)| violations.push(Violation::PoweredCoset { k, index }));
+
+        if !is_distinct(&coset) {
+            violations.push(Violation::DuplicatedDomain { k, depth: None });
+        }
+
+        for (depth, tree) in self.trees.iter().enumerate() {
+            let (s, s_prime) = &tree.domain;
+            let size = 1 << (k - depth - 1);
+            let factor_size = if depth == k - 1 { 0 } else { size >> 1 };
+
+            if s.len() != size
+                || s_prime.len() != size
+                || tree.factor.len() != factor_size
+                || tree.inv_factor.len() != factor_size
+            {
+                violations.push(Violation::Size {
+                    k,
+                    depth: Some(depth),
+                });
+                return violations;
+            }
+
+            if !is_distinct(&[&s[..], &s_prime[..]].concat()) {
+                violations.push(Violation::DuplicatedDomain {
+                    k,
+                    depth: Some(depth),
+                });
+            }
+        }
+
+        let (s, s_prime) = &self.trees[0].domain;
+        let is_coset_domain = coset
+            .chunks(2)
+            .zip(s.iter().zip(s_prime.iter()))
+            .all(|(points, (a, b))| points[0] == *a && points[1] == *b);
+        if !is_coset_domain {
+            violations.push(Violation::CosetDomain { k });
+        }
+
+        for depth in 0..(k - 1) {
+            let tree = &self.trees[depth];
+            let (s, s_prime) = &tree.domain;
+            let half_n = s.len() >> 1;
+            let isogeny = Isogeny::new(depth + 1);
+
+            // isogeny halves the domain and poles are reported instead of inverted
+            let image = isogeny.images(s);
+            let image_prime = isogeny.images(s_prime);
+            (0..s.len())
+                .filter(|i| image[*i].is_none() || image_prime[*i].is_none())
+                .for_each(|index| violations.push(Violation::IsogenyPole { k, depth, index }));
+            (0..half_n)
+                .filter(|i| {
+                    let is_pair = |image: &[Option<Fp>]| match (image[*i], image[i + half_n]) {
+                        (Some(a), Some(b)) => a == b,
+                        _ => true,
+                    };
+                    !is_pair(&image) || !is_pair(&image_prime)
+                })
+                .for_each(|index| violations.push(Violation::IsogenyPair { k, depth, index }));
+
+            let (next_s, next_s_prime) = &self.trees[depth + 1].domain;
+            let is_image = |image: &[Option<Fp>], next: &[Fp]| {
+                image.iter().zip(next.iter()).all(|(a, b)| *a == Some(*b))
+            };
+            if !is_image(&image, next_s) || !is_image(&image_prime, next_s_prime) {
+                violations.push(Violation::IsogenyImage { k, depth });
+            }
+
+            // factors are the isogeny decomposition matrices on s_prime and inverse on s
+            let expected = isogeny.get_factor(s_prime, half_n);
+            let (expected, factor) = (expected.view(s_prime, false), tree.get_factor());
+            (0..half_n)
+                .filter(|i| expected.entry(*i) != factor.entry(*i))
+                .for_each(|index| violations.push(Violation::Factor { k, depth, index }));
+
+            let matrix = isogeny.get_factor(s, half_n);
+            let (matrix, inv_factor) = (matrix.view(s, false), tree.get_inv_factor());
+            (0..half_n)
+                .filter(|i| !is_identity(&inv_factor.entry(*i), &matrix.entry(*i)))
+                .for_each(|index| violations.push(Violation::InvFactor { k, depth, index }));
+        }
+
+        violations
+    }
+}
+
+fn is_distinct(points: &[Fp]) -> bool {
+    let mut points = points.to_vec();
+    points.sort();
+    points.windows(2).all(|pair| pair[0] != pair[1])
+}
+
+// whether product of two matrices is the identity
+fn is_identity(
+    ((a, b), (c, d)): &((Fp, Fp), (Fp, Fp)),
+    ((e, f), (g, h)): &((Fp, Fp), (Fp, Fp)),
+) -> bool {
+    *a * e + *b * g == Fp::one()
+        && *a * f + *b * h == Fp::zero()
+        && *c * e + *d * g == Fp::zero()
+        && *c * f + *d * h == Fp::one()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Violation;
+    use crate::ecfft::fftree::{FactorMatrix, Table};
+    use crate::ecfft::field::Fp;
+    use crate::ecfft::isogeny::Isogeny;
+    use crate::ecfft::utils::EcFftCache;
+    use crate::test::layer_coset;
+
+    fn entries(factor: &mut FactorMatrix) -> &mut [Table; 4] {
+        match factor {
+            FactorMatrix::Full(entries) => entries,
+            FactorMatrix::Compact(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_detect_broken_params() {
+        let k = 8;
+        let params = EcFftCache::new(k, layer_coset(14 - k));
+        assert!(params.verify().is_empty());
+
+        let mut broken = params.clone();
+        broken.powered_coset.to_mut()[3] += Fp::one();
+        assert_eq!(
+            broken.verify(),
+            vec![Violation::PoweredCoset { k, index: 3 }]
+        );
+
+        let mut broken = params.clone();
+        entries(&mut broken.trees.to_mut()[2].factor)[1].to_mut()[1] += Fp::one();
+        assert_eq!(
+            broken.verify(),
+            vec![Violation::Factor {
+                k,
+                depth: 2,
+                index: 1
+            }]
+        );
+
+        let mut broken = params.clone();
+        entries(&mut broken.trees.to_mut()[1].inv_factor)[2].to_mut()[5] += Fp::one();
+        assert_eq!(
+            broken.verify(),
+            vec![Violation::InvFactor {
+                k,
+                depth: 1,
+                index: 5
+            }]
+        );
+
+        let mut broken = params.clone();
+        let tree = &mut broken.trees.to_mut()[3];
+        tree.domain.0.to_mut()[0] = tree.domain.1[0];
+        let violations = broken.verify();
+        assert!(violations.contains(&Violation::DuplicatedDomain { k, depth: Some(3) }));
+        assert!(violations.contains(&Violation::IsogenyImage { k, depth: 2 }));
+
+        // a domain point on the pole of the isogeny is reported rather than inverted
+        let mut broken = params.clone();
+        let pole = -Isogeny::new(3).evaluate_with_denominator(Fp::zero());
+        broken.trees.to_mut()[2].domain.1.to_mut()[4] = pole;
+        let violations = broken.verify();
+        assert!(violations.contains(&Violation::IsogenyPole {
+            k,
+            depth: 2,
+            index: 4
+        }));
+
+        let mut broken = params;
+        entries(&mut broken.trees.to_mut()[4].factor)[0]
+            .to_mut()
+            .pop();
+        assert_eq!(broken.verify(), vec![Violation::Size { k, depth: Some(4) }]);
+    }
+}
@@ -4,14 +4,18 @@ mod test;
 mod classic_fft;
 mod ecfft;
 mod polynomial;
+mod utils;
 
-pub use crate::ecfft::EcFft;
-pub use classic_fft::ClassicFft;
-pub use polynomial::{Coefficients, Polynomial};
+pub use crate::ecfft::{EcFft, EcFftMultiplier, Scratch, VerifyReport, Violation};
+pub use classic_fft::{ClassicFft, FftMultiplier};
+pub use polynomial::{
+    Coefficients, Domain, Karatsuba, Multiplier, PointValue, Polynomial, SharedPoints,
+    SparsePolynomial,
+};
 
 #[cfg(test)]
 mod tests {
-    use super::{ClassicFft, EcFft};
+    use super::{ClassicFft, Coefficients, EcFft, Polynomial};
     use crate::test::{arb_poly_fq, arb_poly_fr, point_multiply_fr};
     use pairing::bn256::Fr;
     use proptest::prelude::*;
@@ -37,7 +41,8 @@ mod tests {
             let mut poly_f = point_multiply_fr(poly_c, poly_d);
             classic_fft.idft(&mut poly_f);
 
-            assert_eq!(poly_e.get_values(), poly_f)
+            // the product has 2^k - 1 coefficients and fft one more zero
+            assert_eq!(poly_e, Polynomial::<Fr, Coefficients>::new(poly_f))
         }
     }
 
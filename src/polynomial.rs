@@ -1,67 +1,132 @@
+mod composition;
+mod gcd;
+mod shared;
+mod sparse;
+
+pub(crate) use gcd::short_multiply;
+pub use gcd::{Karatsuba, Multiplier};
+pub use shared::SharedPoints;
+pub use sparse::SparsePolynomial;
+
 use pairing::group::ff::Field;
 
+use std::borrow::Cow;
 use std::fmt::Debug;
 use std::marker::PhantomData;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::sync::Arc;
 
 /// The basis over which a polynomial is described.
-pub trait Basis: Copy + Debug + Send + Sync {}
+pub trait Basis<F>: Copy + Debug + Send + Sync {
+    /// The points where values are taken, nothing for coefficients
+    type Domain: Clone + Debug;
+}
 
 /// The polynomial coefficient representation
 #[derive(Clone, Copy, Debug)]
 pub struct Coefficients;
-impl Basis for Coefficients {}
+impl<F> Basis<F> for Coefficients {
+    type Domain = ();
+}
 
 /// The polynomial point-value representation
 #[derive(Clone, Copy, Debug)]
 pub struct PointValue;
-impl Basis for PointValue {}
+impl<F: Clone + Debug + 'static> Basis<F> for PointValue {
+    type Domain = Domain<F>;
+}
 
+/// The points over which a point-value polynomial is evaluated
 #[derive(Clone, Debug)]
-pub struct Polynomial<F, B> {
-    pub(crate) values: Vec<F>,
-    pub(crate) _marker: PhantomData<B>,
+pub enum Domain<F: 'static> {
+    /// The ecfft coset of 2^k size
+    EcFftCoset(usize, SharedPoints<F>),
+    /// The 2^k-th roots of unity generated by the element
+    RootsOfUnity(usize, F),
+    /// Arbitrary points
+    Points(Arc<Vec<F>>),
 }
 
-impl<F: Field, B: Basis> Polynomial<F, B> {
-    pub fn new(coeffs: Vec<F>) -> Polynomial<F, Coefficients> {
-        Polynomial {
-            values: coeffs,
-            _marker: PhantomData,
+impl<F: Field> Domain<F> {
+    pub fn len(&self) -> usize {
+        match self {
+            Domain::EcFftCoset(_, points) => points.len(),
+            Domain::Points(points) => points.len(),
+            Domain::RootsOfUnity(k, _) => 1 << k,
         }
     }
 
-    pub fn get_values(self) -> Vec<F> {
-        self.values
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 
-    // order(n) polynomials points multiplication
-    pub fn point_multiply(self, b: Polynomial<F, PointValue>) -> Polynomial<F, PointValue> {
-        let values = self
-            .values
-            .iter()
-            .zip(b.values.iter())
-            .map(|(a, b)| *a * *b)
-            .collect::<Vec<_>>();
-        Polynomial {
-            values,
-            _marker: PhantomData,
+    // points in evaluation order, roots of unity are computed on demand
+    pub fn points(&self) -> Cow<'_, [F]> {
+        match self {
+            Domain::EcFftCoset(_, points) => Cow::Borrowed(points),
+            Domain::Points(points) => Cow::Borrowed(points),
+            Domain::RootsOfUnity(k, omega) => Cow::Owned(
+                (0..1 << k)
+                    .scan(F::one(), |w, _| {
+                        let point = *w;
+                        *w *= omega;
+                        Some(point)
+                    })
+                    .collect(),
+            ),
         }
     }
+}
 
-    // order(n^2) polynomials coefficients multiplication
-    pub fn naive_multiply(self, b: Polynomial<F, Coefficients>) -> Polynomial<F, PointValue> {
-        let mut c = vec![F::zero(); self.values.len() + b.values.len()];
-        self.values.iter().enumerate().for_each(|(i_a, coeff_a)| {
-            b.values.iter().enumerate().for_each(|(i_b, coeff_b)| {
-                c[i_a + i_b] += *coeff_a * *coeff_b;
-            })
-        });
+// domains are equal if they consist of the same points
+// the same kinds are compared by their identifiers and shared points by reference first
+impl<F: Field> PartialEq for Domain<F> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Domain::EcFftCoset(k, a), Domain::EcFftCoset(other_k, b)) => k == other_k && a == b,
+            (Domain::RootsOfUnity(k, omega), Domain::RootsOfUnity(other_k, other_omega)) => {
+                k == other_k && omega == other_omega
+            }
+            (Domain::RootsOfUnity(k, omega), other) | (other, Domain::RootsOfUnity(k, omega)) => {
+                other.len() == 1 << k && is_powers(&other.points(), *omega)
+            }
+            (Domain::Points(a), Domain::Points(b)) if Arc::ptr_eq(a, b) => true,
+            _ => self.points() == other.points(),
+        }
+    }
+}
+
+// whether points are the successive powers of omega from one
+// roots are walked along the points so that different ones return early without generating all
+fn is_powers<F: Field>(points: &[F], omega: F) -> bool {
+    let mut power = F::one();
+    points.iter().all(|point| {
+        let is_equal = *point == power;
+        power *= omega;
+        is_equal
+    })
+}
+
+#[derive(Clone, Debug)]
+pub struct Polynomial<F: 'static, B: Basis<F>> {
+    pub(crate) values: Vec<F>,
+    pub(crate) domain: B::Domain,
+    pub(crate) _marker: PhantomData<B>,
+}
+
+impl<F: Field, B: Basis<F>> Polynomial<F, B> {
+    pub fn new(coeffs: Vec<F>) -> Polynomial<F, Coefficients> {
         Polynomial {
-            values: c,
+            values: coeffs,
+            domain: (),
             _marker: PhantomData,
         }
     }
 
+    pub fn get_values(self) -> Vec<F> {
+        self.values
+    }
+
     // order(n) polynomials points multiplication
     pub fn polynomial_evaluation(self, x: F) -> F {
         self.values
@@ -71,33 +136,311 @@ impl<F: Field, B: Basis> Polynomial<F, B> {
     }
 
     // order(n^2) transform coeffitient to point value representation
-    pub fn to_point_value(&self, domain: &Vec<F>) -> Polynomial<F, PointValue> {
+    pub fn to_point_value(&self, domain: &[F]) -> Polynomial<F, PointValue> {
         assert_eq!(self.values.len(), domain.len());
         let values = domain
             .iter()
             .map(|x| self.clone().polynomial_evaluation(*x))
             .collect::<Vec<_>>();
+        Polynomial::from_evaluations(values, Domain::Points(Arc::new(domain.to_vec())))
+    }
+}
+
+impl<F: Field> Polynomial<F, PointValue> {
+    pub fn from_evaluations(values: Vec<F>, domain: Domain<F>) -> Self {
+        assert_eq!(values.len(), domain.len());
         Polynomial {
             values,
+            domain,
             _marker: PhantomData,
         }
     }
+
+    pub fn domain(&self) -> &Domain<F> {
+        &self.domain
+    }
+
+    // evaluation points of each value
+    pub fn points(&self) -> Cow<'_, [F]> {
+        self.domain.points()
+    }
+
+    // order(n) polynomials points multiplication
+    pub fn point_multiply(mut self, b: Polynomial<F, PointValue>) -> Polynomial<F, PointValue> {
+        assert_same_domain(&mut self, &b);
+        self.values
+            .iter_mut()
+            .zip(b.values.iter())
+            .for_each(|(a, b)| *a *= b);
+        self
+    }
+}
+
+impl<F: Field> Polynomial<F, Coefficients> {
+    // highest index of non zero coefficients, none for zero polynomial
+    pub fn degree(&self) -> Option<usize> {
+        self.values
+            .iter()
+            .rposition(|coeff| !bool::from(coeff.is_zero()))
+    }
+
+    pub fn leading_coefficient(&self) -> Option<F> {
+        self.degree().map(|degree| self.values[degree])
+    }
+
+    // remove trailing zero coefficients
+    pub fn trim(&mut self) {
+        let len = self.degree().map_or(0, |degree| degree + 1);
+        self.values.truncate(len);
+    }
+
+    // order(n^2) polynomials coefficients multiplication
+    // the product of a and b size polynomials has a + b - 1 coefficients
+    pub fn naive_multiply(self, b: Polynomial<F, Coefficients>) -> Polynomial<F, Coefficients> {
+        if self.values.is_empty() || b.values.is_empty() {
+            return Polynomial::<F, Coefficients>::new(Vec::new());
+        }
+
+        let mut c = vec![F::zero(); self.values.len() + b.values.len() - 1];
+        self.values.iter().enumerate().for_each(|(i_a, coeff_a)| {
+            b.values.iter().enumerate().for_each(|(i_b, coeff_b)| {
+                c[i_a + i_b] += *coeff_a * *coeff_b;
+            })
+        });
+        Polynomial::<F, Coefficients>::new(c)
+    }
+}
+
+// coefficients are equal up to trailing zeros
+impl<F: Field> PartialEq for Polynomial<F, Coefficients> {
+    fn eq(&self, other: &Self) -> bool {
+        let (a, b) = if self.values.len() < other.values.len() {
+            (&self.values, &other.values)
+        } else {
+            (&other.values, &self.values)
+        };
+        a[..] == b[..a.len()] && b[a.len()..].iter().all(|coeff| bool::from(coeff.is_zero()))
+    }
 }
 
-impl<F: Field, B: Basis> PartialEq for Polynomial<F, B> {
+impl<F: Field> PartialEq for Polynomial<F, PointValue> {
     fn eq(&self, other: &Self) -> bool {
-        self.values == other.values
+        self.values == other.values && self.domain == other.domain
     }
 }
 
+// coefficients of different size are added as zero padded
+fn pad_to_same_size<F: Field>(
+    a: &mut Polynomial<F, Coefficients>,
+    b: &Polynomial<F, Coefficients>,
+) {
+    if a.values.len() < b.values.len() {
+        a.values.resize(b.values.len(), F::zero());
+    }
+}
+
+// point values are only combined on the same domain
+fn assert_same_domain<F: Field>(a: &mut Polynomial<F, PointValue>, b: &Polynomial<F, PointValue>) {
+    assert_eq!(
+        a.values.len(),
+        b.values.len(),
+        "point values have different sizes"
+    );
+    assert!(
+        a.domain == b.domain,
+        "point values are on different domains"
+    );
+}
+
+// coefficient vectors of the submodules are kept without trailing zeros
+fn add<F: Field>(a: &[F], b: &[F]) -> Vec<F> {
+    trimmed(&padded_add(a, b))
+}
+
+// sum of the size of the longer one
+fn padded_add<F: Field>(a: &[F], b: &[F]) -> Vec<F> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut c = long.to_vec();
+    c.iter_mut().zip(short.iter()).for_each(|(c, s)| *c += s);
+    c
+}
+
+// difference of the size of the longer one trimmed
+fn sub<F: Field>(a: &[F], b: &[F]) -> Vec<F> {
+    let mut c = a.to_vec();
+    if c.len() < b.len() {
+        c.resize(b.len(), F::zero());
+    }
+    c.iter_mut().zip(b.iter()).for_each(|(c, b)| *c -= b);
+    trimmed(&c)
+}
+
+fn trimmed<F: Field>(values: &[F]) -> Vec<F> {
+    let len = values
+        .iter()
+        .rposition(|value| !bool::from(value.is_zero()))
+        .map_or(0, |degree| degree + 1);
+    values[..len].to_vec()
+}
+
+// arithmetic between polynomials of the same basis
+// every variant is derived from the assignment by reference
+macro_rules! impl_ops {
+    ($basis:ty, $resize:ident) => {
+        impl<'a, F: Field> AddAssign<&'a Polynomial<F, $basis>> for Polynomial<F, $basis> {
+            fn add_assign(&mut self, rhs: &'a Polynomial<F, $basis>) {
+                $resize(self, rhs);
+                self.values
+                    .iter_mut()
+                    .zip(rhs.values.iter())
+                    .for_each(|(a, b)| *a += b);
+            }
+        }
+
+        impl<'a, F: Field> SubAssign<&'a Polynomial<F, $basis>> for Polynomial<F, $basis> {
+            fn sub_assign(&mut self, rhs: &'a Polynomial<F, $basis>) {
+                $resize(self, rhs);
+                self.values
+                    .iter_mut()
+                    .zip(rhs.values.iter())
+                    .for_each(|(a, b)| *a -= b);
+            }
+        }
+
+        impl<F: Field> AddAssign<Polynomial<F, $basis>> for Polynomial<F, $basis> {
+            fn add_assign(&mut self, rhs: Polynomial<F, $basis>) {
+                *self += &rhs;
+            }
+        }
+
+        impl<F: Field> SubAssign<Polynomial<F, $basis>> for Polynomial<F, $basis> {
+            fn sub_assign(&mut self, rhs: Polynomial<F, $basis>) {
+                *self -= &rhs;
+            }
+        }
+
+        impl<F: Field> MulAssign<F> for Polynomial<F, $basis> {
+            fn mul_assign(&mut self, rhs: F) {
+                self.values.iter_mut().for_each(|a| *a *= rhs);
+            }
+        }
+
+        impl<'a, F: Field> Add<&'a Polynomial<F, $basis>> for Polynomial<F, $basis> {
+            type Output = Polynomial<F, $basis>;
+
+            fn add(mut self, rhs: &'a Polynomial<F, $basis>) -> Self::Output {
+                self += rhs;
+                self
+            }
+        }
+
+        impl<F: Field> Add<Polynomial<F, $basis>> for Polynomial<F, $basis> {
+            type Output = Polynomial<F, $basis>;
+
+            fn add(mut self, rhs: Polynomial<F, $basis>) -> Self::Output {
+                self += &rhs;
+                self
+            }
+        }
+
+        impl<'a, 'b, F: Field> Add<&'b Polynomial<F, $basis>> for &'a Polynomial<F, $basis> {
+            type Output = Polynomial<F, $basis>;
+
+            fn add(self, rhs: &'b Polynomial<F, $basis>) -> Self::Output {
+                self.clone() + rhs
+            }
+        }
+
+        impl<'a, F: Field> Add<Polynomial<F, $basis>> for &'a Polynomial<F, $basis> {
+            type Output = Polynomial<F, $basis>;
+
+            fn add(self, rhs: Polynomial<F, $basis>) -> Self::Output {
+                self.clone() + &rhs
+            }
+        }
+
+        impl<'a, F: Field> Sub<&'a Polynomial<F, $basis>> for Polynomial<F, $basis> {
+            type Output = Polynomial<F, $basis>;
+
+            fn sub(mut self, rhs: &'a Polynomial<F, $basis>) -> Self::Output {
+                self -= rhs;
+                self
+            }
+        }
+
+        impl<F: Field> Sub<Polynomial<F, $basis>> for Polynomial<F, $basis> {
+            type Output = Polynomial<F, $basis>;
+
+            fn sub(mut self, rhs: Polynomial<F, $basis>) -> Self::Output {
+                self -= &rhs;
+                self
+            }
+        }
+
+        impl<'a, 'b, F: Field> Sub<&'b Polynomial<F, $basis>> for &'a Polynomial<F, $basis> {
+            type Output = Polynomial<F, $basis>;
+
+            fn sub(self, rhs: &'b Polynomial<F, $basis>) -> Self::Output {
+                self.clone() - rhs
+            }
+        }
+
+        impl<'a, F: Field> Sub<Polynomial<F, $basis>> for &'a Polynomial<F, $basis> {
+            type Output = Polynomial<F, $basis>;
+
+            fn sub(self, rhs: Polynomial<F, $basis>) -> Self::Output {
+                self.clone() - &rhs
+            }
+        }
+
+        impl<F: Field> Neg for Polynomial<F, $basis> {
+            type Output = Polynomial<F, $basis>;
+
+            fn neg(mut self) -> Self::Output {
+                self.values.iter_mut().for_each(|a| *a = -*a);
+                self
+            }
+        }
+
+        impl<'a, F: Field> Neg for &'a Polynomial<F, $basis> {
+            type Output = Polynomial<F, $basis>;
+
+            fn neg(self) -> Self::Output {
+                -self.clone()
+            }
+        }
+
+        impl<F: Field> Mul<F> for Polynomial<F, $basis> {
+            type Output = Polynomial<F, $basis>;
+
+            fn mul(mut self, rhs: F) -> Self::Output {
+                self *= rhs;
+                self
+            }
+        }
+
+        impl<'a, F: Field> Mul<F> for &'a Polynomial<F, $basis> {
+            type Output = Polynomial<F, $basis>;
+
+            fn mul(self, rhs: F) -> Self::Output {
+                self.clone() * rhs
+            }
+        }
+    };
+}
+
+impl_ops!(Coefficients, pad_to_same_size);
+impl_ops!(PointValue, assert_same_domain);
+
 #[cfg(test)]
 mod tests {
-    use super::{Coefficients, Polynomial};
+    use super::{Coefficients, Domain, Polynomial};
     use pairing::arithmetic::BaseExt;
     use pairing::bn256::Fq;
     use pairing::group::ff::Field;
     use proptest::{collection::vec, prelude::*};
     use rand_core::OsRng;
+    use std::sync::Arc;
 
     fn arb_poly(k: u32) -> Polynomial<Fq, Coefficients> {
         Polynomial::<Fq, Coefficients>::new(
@@ -113,6 +456,144 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_degree_and_trim() {
+        let mut poly = arb_poly(3);
+        let leading = poly.values[5];
+        poly.values[6] = Fq::zero();
+        poly.values[7] = Fq::zero();
+        let untrimmed = poly.clone();
+
+        assert_eq!(poly.degree(), Some(5));
+        assert_eq!(poly.leading_coefficient(), Some(leading));
+        poly.trim();
+        assert_eq!(poly.values.len(), 6);
+        assert_eq!(poly, untrimmed);
+        assert_eq!(untrimmed, poly);
+
+        let mut zero = Polynomial::<Fq, Coefficients>::new(vec![Fq::zero(); 4]);
+        assert_eq!(zero.degree(), None);
+        assert_eq!(zero.leading_coefficient(), None);
+        zero.trim();
+        assert!(zero.values.is_empty());
+        assert_eq!(zero, Polynomial::<Fq, Coefficients>::new(vec![Fq::zero()]));
+        assert_ne!(poly, zero);
+    }
+
+    #[test]
+    fn test_naive_multiply() {
+        let (a, b) = (arb_poly(3), arb_poly(2));
+        let x = Fq::random(OsRng);
+        let product = a.clone().naive_multiply(b.clone());
+
+        assert_eq!(product.values.len(), 8 + 4 - 1);
+        assert_eq!(product.degree(), Some(7 + 3));
+        assert_eq!(
+            product.polynomial_evaluation(x),
+            a.clone().polynomial_evaluation(x) * b.polynomial_evaluation(x)
+        );
+        assert!(a
+            .naive_multiply(Polynomial::<Fq, Coefficients>::new(Vec::new()))
+            .values
+            .is_empty());
+    }
+
+    #[test]
+    fn test_coefficients_ops() {
+        let (a, b) = (arb_poly(4), arb_poly(3));
+        let (x, scalar) = (Fq::random(OsRng), Fq::random(OsRng));
+        let (eval_a, eval_b) = (
+            a.clone().polynomial_evaluation(x),
+            b.clone().polynomial_evaluation(x),
+        );
+
+        // different sizes are zero padded
+        let sum = &a + &b;
+        assert_eq!(sum.values.len(), 1 << 4);
+        assert_eq!(sum.clone().polynomial_evaluation(x), eval_a + eval_b);
+        assert_eq!((&b - &a).polynomial_evaluation(x), eval_b - eval_a);
+        assert_eq!((-&a).polynomial_evaluation(x), -eval_a);
+        assert_eq!((&a * scalar).polynomial_evaluation(x), eval_a * scalar);
+        assert_eq!(sum - b.clone(), a);
+
+        let mut c = b.clone();
+        c += a.clone();
+        c -= &b;
+        c *= scalar;
+        assert_eq!(c, a.clone() * scalar);
+        assert_eq!(a.clone() + b.clone(), b + a);
+    }
+
+    fn arb_domain(k: u32) -> Domain<Fq> {
+        Domain::Points(Arc::new(arb_poly(k).values))
+    }
+
+    #[test]
+    fn test_point_value_ops() {
+        let domain = arb_domain(4);
+        let point_value = |values| Polynomial::from_evaluations(values, domain.clone());
+        let (a, b) = (arb_poly(4).values, arb_poly(4).values);
+        let scalar = Fq::random(OsRng);
+        let (poly_a, poly_b) = (point_value(a.clone()), point_value(b.clone()));
+
+        let zip = |f: fn(Fq, Fq) -> Fq| {
+            point_value(a.iter().zip(b.iter()).map(|(a, b)| f(*a, *b)).collect())
+        };
+        assert_eq!(&poly_a + &poly_b, zip(|a, b| a + b));
+        assert_eq!(poly_a.clone() - &poly_b, zip(|a, b| a - b));
+        assert_eq!(
+            poly_a.clone().point_multiply(poly_b.clone()),
+            zip(|a, b| a * b)
+        );
+        assert_eq!(
+            -poly_a.clone() * scalar,
+            point_value(a.iter().map(|a| -*a * scalar).collect())
+        );
+
+        let mut c = poly_a.clone();
+        c += &poly_b;
+        c -= poly_b;
+        assert_eq!(c, poly_a);
+        assert_eq!(poly_a.points(), domain.points());
+    }
+
+    #[test]
+    fn test_domain() {
+        let omega = -Fq::one();
+        let roots = Domain::RootsOfUnity(1, omega);
+        assert_eq!(roots.points(), &[Fq::one(), omega][..]);
+        assert_eq!(roots, Domain::Points(Arc::new(vec![Fq::one(), omega])));
+        assert_ne!(roots, Domain::RootsOfUnity(2, Fq::one()));
+        assert_ne!(roots, Domain::Points(Arc::new(vec![omega, Fq::one()])));
+
+        // cosets of the same size from different params differ by their points
+        let coset = |points: Vec<Fq>| Domain::EcFftCoset(1, points.into());
+        assert_eq!(coset(vec![Fq::one(), omega]), roots);
+        assert_ne!(coset(vec![omega, Fq::one()]), coset(vec![Fq::one(), omega]));
+        assert_ne!(arb_domain(3), arb_domain(3));
+
+        let poly = arb_poly(3);
+        let domain = arb_domain(3);
+        let evals = poly.to_point_value(&domain.points().to_vec());
+        assert_eq!(evals.domain(), &domain);
+    }
+
+    #[test]
+    #[should_panic(expected = "point values have different sizes")]
+    fn test_point_value_size_mismatch() {
+        let a = Polynomial::from_evaluations(arb_poly(4).values, arb_domain(4));
+        let b = Polynomial::from_evaluations(arb_poly(3).values, arb_domain(3));
+        let _ = a + b;
+    }
+
+    #[test]
+    #[should_panic(expected = "point values are on different domains")]
+    fn test_point_value_domain_mismatch() {
+        let a = Polynomial::from_evaluations(arb_poly(4).values, arb_domain(4));
+        let b = Polynomial::from_evaluations(arb_poly(4).values, arb_domain(4));
+        let _ = a.point_multiply(b);
+    }
+
     proptest! {
         #![proptest_config(ProptestConfig::with_cases(100))]
         #[test]
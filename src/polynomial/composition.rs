@@ -0,0 +1,185 @@
+// derivative, composition, taylor shift and scaling of coefficient polynomials
+// products go through the given multiplier so large degrees take the fft of each field
+
+use super::{add, trimmed, Coefficients, Multiplier, Polynomial};
+
+use pairing::group::ff::Field;
+
+impl<F: Field> Polynomial<F, Coefficients> {
+    // formal derivative
+    pub fn derivative(&self) -> Self {
+        let mut index = F::zero();
+        let values = self
+            .values
+            .iter()
+            .skip(1)
+            .map(|coeff| {
+                index += F::one();
+                *coeff * index
+            })
+            .collect();
+        Polynomial::<F, Coefficients>::new(values)
+    }
+
+    // p(c * X) multiplying i-th coefficient by c^i
+    pub fn scale(&self, c: F) -> Self {
+        let values = self
+            .values
+            .iter()
+            .scan(F::one(), |power, coeff| {
+                let value = *coeff * *power;
+                *power *= c;
+                Some(value)
+            })
+            .collect();
+        Polynomial::<F, Coefficients>::new(values)
+    }
+
+    // p(X + c) by one convolution
+    // q_j = 1 / j! * sum_{i >= j} (p_i * i!) * c^{i - j} / (i - j)!
+    pub fn taylor_shift<M: Multiplier<F>>(&self, c: F, multiplier: &M) -> Self {
+        let coeffs = trimmed(&self.values);
+        let n = coeffs.len();
+        if n <= 1 {
+            return Polynomial::<F, Coefficients>::new(coeffs);
+        }
+
+        let (factorials, inv_factorials) = factorials(n);
+        let weighted = coeffs
+            .iter()
+            .zip(factorials.iter())
+            .rev()
+            .map(|(coeff, factorial)| *coeff * factorial)
+            .collect::<Vec<_>>();
+        let powers = inv_factorials
+            .iter()
+            .scan(F::one(), |power, inv_factorial| {
+                let value = *power * inv_factorial;
+                *power *= c;
+                Some(value)
+            })
+            .collect::<Vec<_>>();
+
+        let product = multiplier.multiply(&weighted, &powers);
+        let values = inv_factorials
+            .iter()
+            .enumerate()
+            .map(|(j, inv_factorial)| product[n - 1 - j] * inv_factorial)
+            .collect();
+        Polynomial::<F, Coefficients>::new(values)
+    }
+
+    // p(q(X)) splitting p into halves with powers q^{2^j} in O(M(nm) log n)
+    pub fn compose<M: Multiplier<F>>(&self, other: &Self, multiplier: &M) -> Self {
+        let coeffs = trimmed(&self.values);
+        let mut powers = vec![trimmed(&other.values)];
+        while 1 << powers.len() < coeffs.len() {
+            let square = multiplier.multiply(&powers[powers.len() - 1], &powers[powers.len() - 1]);
+            powers.push(square);
+        }
+        Polynomial::<F, Coefficients>::new(compose(&coeffs, &powers, multiplier))
+    }
+}
+
+// p_low(q) + q^h * p_high(q) for the largest power of two h less than the size
+fn compose<F: Field, M: Multiplier<F>>(coeffs: &[F], powers: &[Vec<F>], multiplier: &M) -> Vec<F> {
+    if coeffs.len() <= 1 {
+        return trimmed(coeffs);
+    }
+
+    let log_h = (usize::BITS - (coeffs.len() - 1).leading_zeros() - 1) as usize;
+    let (low, high) = coeffs.split_at(1 << log_h);
+    let low = compose(low, powers, multiplier);
+    let high = compose(high, powers, multiplier);
+    add(&low, &multiplier.multiply(&high, &powers[log_h]))
+}
+
+// i! and 1 / i! for i < n
+fn factorials<F: Field>(n: usize) -> (Vec<F>, Vec<F>) {
+    let mut index = F::zero();
+    let mut factorials = vec![F::one(); n];
+    for i in 1..n {
+        index += F::one();
+        factorials[i] = factorials[i - 1] * index;
+    }
+
+    let mut inv_factorials = vec![F::one(); n];
+    inv_factorials[n - 1] = factorials[n - 1].invert().unwrap();
+    for i in (1..n).rev() {
+        inv_factorials[i - 1] = inv_factorials[i] * index;
+        index -= F::one();
+    }
+    (factorials, inv_factorials)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::classic_fft::FftMultiplier;
+    use crate::ecfft::EcFftMultiplier;
+    use crate::polynomial::{Karatsuba, Multiplier};
+    use crate::test::arb_poly;
+    use pairing::bn256::{Fq, Fr};
+    use pairing::group::ff::Field;
+    use rand_core::OsRng;
+
+    #[test]
+    fn test_derivative_and_scale() {
+        let (a, b) = (arb_poly::<Fq>(20), arb_poly::<Fq>(13));
+        let (x, c) = (Fq::random(OsRng), Fq::random(OsRng));
+
+        // product rule
+        let product = a.clone().naive_multiply(b.clone());
+        assert_eq!(
+            product.derivative(),
+            a.derivative().naive_multiply(b.clone()) + a.clone().naive_multiply(b.derivative())
+        );
+        assert_eq!(arb_poly::<Fq>(1).derivative().degree(), None);
+
+        assert_eq!(
+            a.scale(c).polynomial_evaluation(x),
+            a.polynomial_evaluation(c * x)
+        );
+    }
+
+    fn check_taylor_shift<F: Field, M: Multiplier<F>>(n: usize, multiplier: &M) {
+        let poly = arb_poly::<F>(n);
+        let (x, c) = (F::random(OsRng), F::random(OsRng));
+        let expected = poly.clone().polynomial_evaluation(x + c);
+
+        let shifted = poly.taylor_shift(c, multiplier);
+        assert_eq!(shifted, poly.taylor_shift(c, &Karatsuba));
+        assert_eq!(shifted.polynomial_evaluation(x), expected);
+    }
+
+    fn check_compose<F: Field, M: Multiplier<F>>(n: usize, m: usize, multiplier: &M) {
+        let (p, q) = (arb_poly::<F>(n), arb_poly::<F>(m));
+        let x = F::random(OsRng);
+        let expected = p
+            .clone()
+            .polynomial_evaluation(q.clone().polynomial_evaluation(x));
+
+        let composed = p.compose(&q, multiplier);
+        let degree = p.degree().map(|n| n * q.degree().unwrap_or(0));
+        assert_eq!(composed.degree(), degree);
+        assert_eq!(composed, p.compose(&q, &Karatsuba));
+        assert_eq!(composed.polynomial_evaluation(x), expected);
+    }
+
+    #[test]
+    fn test_taylor_shift() {
+        let multiplier = EcFftMultiplier::default();
+        for n in [0, 1, 2, 50, 300] {
+            check_taylor_shift::<Fr, _>(n, &FftMultiplier);
+            check_taylor_shift::<Fq, _>(n, &multiplier);
+        }
+    }
+
+    #[test]
+    fn test_compose() {
+        let multiplier = EcFftMultiplier::default();
+        for (n, m) in [(0, 3), (1, 3), (5, 0), (17, 4), (100, 9)] {
+            check_compose::<Fr, _>(n, m, &FftMultiplier);
+            check_compose::<Fq, _>(n, m, &multiplier);
+        }
+    }
+}
@@ -0,0 +1,369 @@
+// polynomial greatest common divisors
+// polynomials are handled as coefficient vectors without trailing zeros so zero one is empty
+// fast gcd follows the half gcd of thull and yap which reduces the degree by half with two
+// recursive calls on the top coefficients in O(M(n) log n) for multiplication cost M(n)
+
+use super::{add, padded_add, sub, trimmed, Coefficients, Polynomial};
+
+use pairing::group::ff::Field;
+
+// multiplication of coefficient vectors used by the fast gcd
+pub trait Multiplier<F: Field> {
+    fn multiply(&self, a: &[F], b: &[F]) -> Vec<F>;
+}
+
+// karatsuba multiplication for any field
+// ecfft and classic fft multipliers are faster for large degrees of the fields they support
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Karatsuba;
+
+// sizes up to this are multiplied naively
+const KARATSUBA_CUTOFF: usize = 32;
+
+// shorter polynomials than this are multiplied by karatsuba instead of fft
+const FFT_MULTIPLY_CUTOFF: usize = 64;
+
+// degrees below this are reduced by euclidean steps
+const HALF_GCD_CUTOFF: usize = 64;
+
+impl<F: Field> Multiplier<F> for Karatsuba {
+    fn multiply(&self, a: &[F], b: &[F]) -> Vec<F> {
+        if a.is_empty() || b.is_empty() {
+            return Vec::new();
+        }
+        let mut c = vec![F::zero(); a.len() + b.len() - 1];
+        karatsuba(a, b, &mut c);
+        c
+    }
+}
+
+// product by karatsuba if either polynomial is too short for fft multipliers to pay off
+pub(crate) fn short_multiply<F: Field>(a: &[F], b: &[F]) -> Option<Vec<F>> {
+    if a.len().min(b.len()) <= FFT_MULTIPLY_CUTOFF {
+        Some(Karatsuba.multiply(a, b))
+    } else {
+        None
+    }
+}
+
+// c += a * b where c has a.len() + b.len() - 1 size
+fn karatsuba<F: Field>(a: &[F], b: &[F], c: &mut [F]) {
+    if a.len().min(b.len()) <= KARATSUBA_CUTOFF {
+        a.iter().enumerate().for_each(|(i, a)| {
+            b.iter()
+                .zip(c[i..].iter_mut())
+                .for_each(|(b, c)| *c += *a * b)
+        });
+        return;
+    }
+
+    let half = a.len().max(b.len()) / 2;
+    // split only the longer one if the other fits in the half
+    if a.len() <= half || b.len() <= half {
+        let (long, short) = if a.len() > b.len() { (a, b) } else { (b, a) };
+        let (low, high) = long.split_at(half);
+        karatsuba(low, short, &mut c[..low.len() + short.len() - 1]);
+        karatsuba(high, short, &mut c[half..]);
+        return;
+    }
+
+    // (a0 + a1 x^h)(b0 + b1 x^h) = z0 + ((a0 + a1)(b0 + b1) - z0 - z2) x^h + z2 x^2h
+    let (a0, a1) = a.split_at(half);
+    let (b0, b1) = b.split_at(half);
+    let product = |a: &[F], b: &[F]| {
+        let mut c = vec![F::zero(); a.len() + b.len() - 1];
+        karatsuba(a, b, &mut c);
+        c
+    };
+    let (z0, z2) = (product(a0, b0), product(a1, b1));
+    // sums are not trimmed as they may cancel out to zero
+    let z1 = product(&padded_add(a0, a1), &padded_add(b0, b1));
+
+    z0.iter().zip(c.iter_mut()).for_each(|(z, c)| *c += z);
+    z2.iter()
+        .zip(c[2 * half..].iter_mut())
+        .for_each(|(z, c)| *c += z);
+    c[half..]
+        .iter_mut()
+        .zip(z1.iter())
+        .zip(z0.iter().map(Some).chain(std::iter::repeat(None)))
+        .zip(z2.iter().map(Some).chain(std::iter::repeat(None)))
+        .for_each(|(((c, z1), z0), z2)| {
+            *c += z1;
+            if let Some(z0) = z0 {
+                *c -= z0;
+            }
+            if let Some(z2) = z2 {
+                *c -= z2;
+            }
+        });
+}
+
+impl<F: Field> Polynomial<F, Coefficients> {
+    // quotient and remainder of division by non zero divisor
+    pub fn div_rem(&self, divisor: &Self) -> (Self, Self) {
+        let (quotient, remainder) = div_rem(&trimmed(&self.values), &trimmed(&divisor.values));
+        (
+            Polynomial::<F, Coefficients>::new(quotient),
+            Polynomial::<F, Coefficients>::new(remainder),
+        )
+    }
+
+    // monic greatest common divisor, zero if both are zero
+    pub fn gcd(&self, other: &Self) -> Self {
+        self.xgcd(other).0
+    }
+
+    // monic gcd g and bezout coefficients s and t such that s * self + t * other = g
+    pub fn xgcd(&self, other: &Self) -> (Self, Self, Self) {
+        let (mut a, mut b) = (trimmed(&self.values), trimmed(&other.values));
+        let mut matrix = identity();
+        while !b.is_empty() {
+            let (quotient, remainder) = div_rem(&a, &b);
+            euclidean_step(&mut matrix, &quotient, &Karatsuba);
+            a = std::mem::replace(&mut b, remainder);
+        }
+        bezout(a, matrix)
+    }
+
+    // gcd by half gcd in O(M(n) log n)
+    pub fn fast_gcd<M: Multiplier<F>>(&self, other: &Self, multiplier: &M) -> Self {
+        self.fast_xgcd(other, multiplier).0
+    }
+
+    // xgcd by half gcd in O(M(n) log n)
+    pub fn fast_xgcd<M: Multiplier<F>>(&self, other: &Self, multiplier: &M) -> (Self, Self, Self) {
+        let (mut a, mut b) = (trimmed(&self.values), trimmed(&other.values));
+        let mut matrix = identity();
+        while !b.is_empty() {
+            // half gcd needs deg a > deg b
+            if a.len() > b.len() {
+                let half = half_gcd(&a, &b, multiplier);
+                let (c, d) = apply(&half, &a, &b, multiplier);
+                matrix = mat_mul(&half, &matrix, multiplier);
+                a = c;
+                b = d;
+                if b.is_empty() {
+                    break;
+                }
+            }
+            let (quotient, remainder) = div_rem(&a, &b);
+            euclidean_step(&mut matrix, &quotient, multiplier);
+            a = std::mem::replace(&mut b, remainder);
+        }
+        bezout(a, matrix)
+    }
+}
+
+// 2x2 polynomial matrix mapping (a, b) to the pair of remainders
+type Matrix<F> = [[Vec<F>; 2]; 2];
+
+fn identity<F: Field>() -> Matrix<F> {
+    [[vec![F::one()], Vec::new()], [Vec::new(), vec![F::one()]]]
+}
+
+// matrix which maps (a, b) to (c, d) with deg c >= m > deg d for m = ceil(deg a / 2)
+// c and d are consecutive remainders of euclidean algorithm on a and b with deg a > deg b
+fn half_gcd<F: Field, M: Multiplier<F>>(a: &[F], b: &[F], multiplier: &M) -> Matrix<F> {
+    let n = a.len() - 1;
+    let m = n - n / 2;
+    if b.len() <= m {
+        return identity();
+    }
+    if n < HALF_GCD_CUTOFF {
+        return naive_half_gcd(a, b, m, multiplier);
+    }
+
+    // quotients of the top coefficients agree with the ones of a and b to the half
+    let mut matrix = half_gcd(&a[m..], &b[m..], multiplier);
+    let (a, b) = apply(&matrix, a, b, multiplier);
+    if b.len() <= m {
+        return matrix;
+    }
+
+    let (quotient, remainder) = div_rem(&a, &b);
+    euclidean_step(&mut matrix, &quotient, multiplier);
+    let (c, d) = (b, remainder);
+    if d.len() <= m {
+        return matrix;
+    }
+
+    // top coefficients of c whose half gcd reduces to m
+    let k = (2 * m).saturating_sub(c.len() - 1);
+    let second = half_gcd(&c[k..], &d[k.min(d.len())..], multiplier);
+    mat_mul(&second, &matrix, multiplier)
+}
+
+fn naive_half_gcd<F: Field, M: Multiplier<F>>(
+    a: &[F],
+    b: &[F],
+    m: usize,
+    multiplier: &M,
+) -> Matrix<F> {
+    let (mut a, mut b) = (a.to_vec(), b.to_vec());
+    let mut matrix = identity();
+    while b.len() > m {
+        let (quotient, remainder) = div_rem(&a, &b);
+        euclidean_step(&mut matrix, &quotient, multiplier);
+        a = std::mem::replace(&mut b, remainder);
+    }
+    matrix
+}
+
+// multiply [[0, 1], [1, -q]] from the left
+fn euclidean_step<F: Field, M: Multiplier<F>>(matrix: &mut Matrix<F>, q: &[F], multiplier: &M) {
+    let [first, second] = matrix;
+    for (first, second) in first.iter_mut().zip(second.iter_mut()) {
+        let next = sub(first, &multiplier.multiply(q, second));
+        *first = std::mem::replace(second, next);
+    }
+}
+
+fn apply<F: Field, M: Multiplier<F>>(
+    matrix: &Matrix<F>,
+    a: &[F],
+    b: &[F],
+    multiplier: &M,
+) -> (Vec<F>, Vec<F>) {
+    let row = |[x, y]: &[Vec<F>; 2]| add(&multiplier.multiply(x, a), &multiplier.multiply(y, b));
+    (row(&matrix[0]), row(&matrix[1]))
+}
+
+fn mat_mul<F: Field, M: Multiplier<F>>(x: &Matrix<F>, y: &Matrix<F>, multiplier: &M) -> Matrix<F> {
+    let entry = |i: usize, j: usize| {
+        add(
+            &multiplier.multiply(&x[i][0], &y[0][j]),
+            &multiplier.multiply(&x[i][1], &y[1][j]),
+        )
+    };
+    [[entry(0, 0), entry(0, 1)], [entry(1, 0), entry(1, 1)]]
+}
+
+// normalise gcd to monic with its bezout coefficients
+fn bezout<F: Field>(
+    gcd: Vec<F>,
+    [[s, t], _]: Matrix<F>,
+) -> (
+    Polynomial<F, Coefficients>,
+    Polynomial<F, Coefficients>,
+    Polynomial<F, Coefficients>,
+) {
+    let inv = gcd.last().map_or(F::one(), |lead| lead.invert().unwrap());
+    let monic = |values: Vec<F>| {
+        Polynomial::<F, Coefficients>::new(values.into_iter().map(|value| value * inv).collect())
+    };
+    (monic(gcd), monic(s), monic(t))
+}
+
+fn div_rem<F: Field>(a: &[F], b: &[F]) -> (Vec<F>, Vec<F>) {
+    assert!(!b.is_empty(), "division by zero polynomial");
+    if a.len() < b.len() {
+        return (Vec::new(), a.to_vec());
+    }
+
+    let inv = b[b.len() - 1].invert().unwrap();
+    let mut remainder = a.to_vec();
+    let mut quotient = vec![F::zero(); a.len() - b.len() + 1];
+    for i in (0..quotient.len()).rev() {
+        let coeff = remainder[i + b.len() - 1] * inv;
+        quotient[i] = coeff;
+        remainder[i..i + b.len()]
+            .iter_mut()
+            .zip(b.iter())
+            .for_each(|(r, b)| *r -= coeff * b);
+    }
+    remainder.truncate(b.len() - 1);
+    (quotient, trimmed(&remainder))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{half_gcd, naive_half_gcd, Karatsuba, Multiplier};
+    use crate::ecfft::EcFftMultiplier;
+    use crate::polynomial::{Coefficients, Polynomial};
+    use crate::test::arb_poly;
+    use pairing::bn256::Fq;
+    use pairing::group::ff::Field;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_karatsuba() {
+        for (n, m) in [(1, 1), (33, 33), (100, 7), (64, 200), (129, 128)] {
+            let (a, b) = (arb_poly::<Fq>(n), arb_poly::<Fq>(m));
+            let expected = a.clone().naive_multiply(b.clone()).get_values();
+            assert_eq!(Karatsuba.multiply(&a.values, &b.values), expected);
+        }
+
+        // halves of (1 - x^33)(1 + x^32) cancel out in their sum
+        let mut values = vec![Fq::zero(); 66];
+        values[0] = Fq::one();
+        values[32] = Fq::one();
+        values[33] = -Fq::one();
+        values[65] = -Fq::one();
+        let p = Polynomial::<Fq, Coefficients>::new(values);
+        let expected = p.clone().naive_multiply(p.clone()).get_values();
+        assert_eq!(Karatsuba.multiply(&p.values, &p.values), expected);
+    }
+
+    #[test]
+    fn test_div_rem() {
+        let (a, b) = (arb_poly::<Fq>(50), arb_poly::<Fq>(20));
+        let (quotient, remainder) = a.div_rem(&b);
+        assert_eq!(quotient.degree(), Some(30));
+        assert!(remainder.degree() < b.degree());
+        assert_eq!(quotient.naive_multiply(b.clone()) + remainder, a);
+
+        let (quotient, remainder) = b.div_rem(&a);
+        assert_eq!((quotient.degree(), remainder), (None, b));
+    }
+
+    #[test]
+    fn test_half_gcd() {
+        for n in [70, 129, 300] {
+            let (a, b) = (arb_poly::<Fq>(n).values, arb_poly::<Fq>(n - 1).values);
+            let m = n / 2;
+            assert_eq!(
+                half_gcd(&a, &b, &EcFftMultiplier::default()),
+                naive_half_gcd(&a, &b, m, &Karatsuba)
+            );
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(20))]
+        #[test]
+        fn test_gcd(common in 0usize..40, n in 1usize..200, m in 1usize..200) {
+            let factor = arb_poly::<Fq>(common + 1);
+            let a = factor.clone().naive_multiply(arb_poly::<Fq>(n));
+            let b = factor.clone().naive_multiply(arb_poly::<Fq>(m));
+            let inv = factor.leading_coefficient().unwrap().invert().unwrap();
+            let monic_factor = factor * inv;
+
+            let (gcd, s, t) = a.xgcd(&b);
+            let (fast_gcd, fast_s, fast_t) = a.fast_xgcd(&b, &EcFftMultiplier::default());
+            prop_assert_eq!(&gcd, &monic_factor);
+            prop_assert_eq!(&fast_gcd, &monic_factor);
+            prop_assert_eq!(a.gcd(&b), a.fast_gcd(&b, &EcFftMultiplier::default()));
+
+            // same bezout coefficients of minimal degrees
+            prop_assert_eq!(&s, &fast_s);
+            prop_assert_eq!(&t, &fast_t);
+            let degree = gcd.degree().unwrap();
+            prop_assert!(s.degree().map(|s| s + degree) < b.degree());
+            prop_assert!(t.degree().map(|t| t + degree) < a.degree());
+            prop_assert_eq!(s.naive_multiply(a.clone()) + t.naive_multiply(b.clone()), gcd);
+        }
+    }
+
+    #[test]
+    fn test_gcd_with_zero() {
+        let (a, zero) = (
+            arb_poly::<Fq>(10),
+            Polynomial::<Fq, Coefficients>::new(vec![]),
+        );
+        let monic = a.clone() * a.leading_coefficient().unwrap().invert().unwrap();
+        assert_eq!(a.gcd(&zero), monic);
+        assert_eq!(zero.fast_gcd(&a, &EcFftMultiplier::default()), monic);
+        assert_eq!(zero.gcd(&zero), zero);
+    }
+}
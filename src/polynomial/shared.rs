@@ -0,0 +1,34 @@
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// Points embedded in the binary or shared behind a reference count
+#[derive(Clone, Debug)]
+pub enum SharedPoints<F: 'static> {
+    Static(&'static [F]),
+    Heap(Arc<Vec<F>>),
+}
+
+impl<F> Deref for SharedPoints<F> {
+    type Target = [F];
+
+    fn deref(&self) -> &[F] {
+        match self {
+            SharedPoints::Static(points) => points,
+            SharedPoints::Heap(points) => points,
+        }
+    }
+}
+
+// the same shared points are equal without comparing each of them
+impl<F: PartialEq> PartialEq for SharedPoints<F> {
+    fn eq(&self, other: &Self) -> bool {
+        let is_same = self.as_ptr() == other.as_ptr() && self.len() == other.len();
+        is_same || self[..] == other[..]
+    }
+}
+
+impl<F> From<Vec<F>> for SharedPoints<F> {
+    fn from(points: Vec<F>) -> Self {
+        SharedPoints::Heap(Arc::new(points))
+    }
+}
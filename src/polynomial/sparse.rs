@@ -0,0 +1,201 @@
+// sparse polynomials of (exponent, coefficient) terms
+
+use super::{trimmed, Coefficients, Polynomial};
+
+use pairing::group::ff::Field;
+
+// polynomial of few non zero terms such as X^n - 1
+#[derive(Clone, Debug, PartialEq)]
+pub struct SparsePolynomial<F> {
+    // non zero terms sorted by exponent without duplicates
+    terms: Vec<(usize, F)>,
+}
+
+impl<F: Field> SparsePolynomial<F> {
+    // sum of terms, the coefficients of the same exponent are added
+    pub fn new(mut terms: Vec<(usize, F)>) -> Self {
+        terms.sort_unstable_by_key(|(exponent, _)| *exponent);
+        let mut merged: Vec<(usize, F)> = Vec::with_capacity(terms.len());
+        for (exponent, coeff) in terms {
+            match merged.last_mut() {
+                Some((last, sum)) if *last == exponent => *sum += coeff,
+                _ => merged.push((exponent, coeff)),
+            }
+        }
+        merged.retain(|(_, coeff)| !bool::from(coeff.is_zero()));
+        SparsePolynomial { terms: merged }
+    }
+
+    pub fn terms(&self) -> &[(usize, F)] {
+        &self.terms
+    }
+
+    pub fn degree(&self) -> Option<usize> {
+        self.terms.last().map(|(exponent, _)| *exponent)
+    }
+
+    // order(terms * log(degree)) evaluation powering x by exponent gaps
+    pub fn evaluate(&self, x: F) -> F {
+        let mut power = F::one();
+        let mut prev = 0;
+        self.terms.iter().fold(F::zero(), |acc, (exponent, coeff)| {
+            power *= x.pow_vartime([(exponent - prev) as u64]);
+            prev = *exponent;
+            acc + power * coeff
+        })
+    }
+
+    // order(n * terms) multiplication by dense polynomial
+    pub fn multiply(&self, dense: &Polynomial<F, Coefficients>) -> Polynomial<F, Coefficients> {
+        let dense = trimmed(&dense.values);
+        let degree = match (self.degree(), dense.len()) {
+            (Some(degree), len) if len > 0 => degree,
+            _ => return Polynomial::<F, Coefficients>::new(Vec::new()),
+        };
+
+        let mut values = vec![F::zero(); degree + dense.len()];
+        self.terms.iter().for_each(|(exponent, coeff)| {
+            values[*exponent..]
+                .iter_mut()
+                .zip(dense.iter())
+                .for_each(|(value, dense)| *value += *coeff * dense)
+        });
+        Polynomial::<F, Coefficients>::new(values)
+    }
+}
+
+impl<F: Field> Polynomial<F, Coefficients> {
+    // order(n * terms) quotient and remainder of division by non zero sparse divisor
+    pub fn div_rem_sparse(&self, divisor: &SparsePolynomial<F>) -> (Self, Self) {
+        let (&(degree, lead), lower) = divisor
+            .terms
+            .split_last()
+            .expect("division by zero polynomial");
+        let mut remainder = trimmed(&self.values);
+        if remainder.len() <= degree {
+            return (
+                Polynomial::<F, Coefficients>::new(Vec::new()),
+                Polynomial::<F, Coefficients>::new(remainder),
+            );
+        }
+
+        let inv = lead.invert().unwrap();
+        let mut quotient = vec![F::zero(); remainder.len() - degree];
+        for i in (0..quotient.len()).rev() {
+            let coeff = remainder[i + degree] * inv;
+            quotient[i] = coeff;
+            remainder[i + degree] = F::zero();
+            lower
+                .iter()
+                .for_each(|(exponent, c)| remainder[i + exponent] -= coeff * c);
+        }
+        remainder.truncate(degree);
+        (
+            Polynomial::<F, Coefficients>::new(quotient),
+            Polynomial::<F, Coefficients>::new(trimmed(&remainder)),
+        )
+    }
+}
+
+impl<F: Field> From<&Polynomial<F, Coefficients>> for SparsePolynomial<F> {
+    fn from(dense: &Polynomial<F, Coefficients>) -> Self {
+        let terms = dense
+            .values
+            .iter()
+            .enumerate()
+            .filter(|(_, coeff)| !bool::from(coeff.is_zero()))
+            .map(|(exponent, coeff)| (exponent, *coeff))
+            .collect();
+        SparsePolynomial { terms }
+    }
+}
+
+impl<F: Field> From<&SparsePolynomial<F>> for Polynomial<F, Coefficients> {
+    fn from(sparse: &SparsePolynomial<F>) -> Self {
+        let mut values = vec![F::zero(); sparse.degree().map_or(0, |degree| degree + 1)];
+        sparse
+            .terms
+            .iter()
+            .for_each(|(exponent, coeff)| values[*exponent] = *coeff);
+        Polynomial::<F, Coefficients>::new(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SparsePolynomial;
+    use crate::polynomial::{Coefficients, Polynomial};
+    use crate::test::arb_poly;
+    use pairing::bn256::Fq;
+    use pairing::group::ff::Field;
+    use rand_core::OsRng;
+
+    // X^n - 1
+    fn vanishing(n: usize) -> SparsePolynomial<Fq> {
+        SparsePolynomial::new(vec![(n, Fq::one()), (0, -Fq::one())])
+    }
+
+    #[test]
+    fn test_normalisation_and_conversion() {
+        let c = Fq::random(OsRng);
+        let sparse = SparsePolynomial::new(vec![(7, c), (0, Fq::one()), (7, c), (3, c), (3, -c)]);
+        assert_eq!(sparse.terms(), &[(0, Fq::one()), (7, c.double())]);
+        assert_eq!(sparse.degree(), Some(7));
+
+        let dense = Polynomial::<Fq, Coefficients>::from(&sparse);
+        assert_eq!(dense.values.len(), 8);
+        assert_eq!(SparsePolynomial::from(&dense), sparse);
+        assert_eq!(
+            SparsePolynomial::<Fq>::new(vec![(2, c), (2, -c)]).degree(),
+            None
+        );
+
+        let x = Fq::random(OsRng);
+        assert_eq!(sparse.evaluate(x), dense.polynomial_evaluation(x));
+    }
+
+    #[test]
+    fn test_multiply() {
+        let sparse = SparsePolynomial::new(vec![(40, Fq::random(OsRng)), (3, Fq::random(OsRng))]);
+        let dense = arb_poly::<Fq>(30);
+        assert_eq!(
+            sparse.multiply(&dense),
+            Polynomial::<Fq, Coefficients>::from(&sparse).naive_multiply(dense.clone())
+        );
+        assert_eq!(
+            SparsePolynomial::new(vec![]).multiply(&dense).degree(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_div_rem_sparse() {
+        for (n, len) in [(1, 5), (16, 64), (16, 70), (100, 50)] {
+            let dense = arb_poly::<Fq>(len);
+            let divisor = vanishing(n);
+            let (quotient, remainder) = dense.div_rem_sparse(&divisor);
+
+            assert!(remainder.degree() < Some(n));
+            assert_eq!(divisor.multiply(&quotient) + remainder.clone(), dense);
+            assert_eq!(
+                (quotient, remainder),
+                dense.div_rem(&Polynomial::from(&divisor))
+            );
+        }
+
+        // remainder of lower degree than the divisor has no trailing zeros
+        let mut padded = arb_poly::<Fq>(10).get_values();
+        padded.resize(20, Fq::zero());
+        let (_, remainder) =
+            Polynomial::<Fq, Coefficients>::new(padded).div_rem_sparse(&vanishing(16));
+        assert_eq!(remainder.get_values().len(), 10);
+
+        // multiples of vanishing polynomial are divided exactly
+        let quotient = arb_poly::<Fq>(33);
+        let multiple = vanishing(8).multiply(&quotient);
+        assert_eq!(
+            multiple.div_rem_sparse(&vanishing(8)),
+            (quotient, Polynomial::<Fq, Coefficients>::new(vec![]))
+        );
+    }
+}
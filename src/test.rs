@@ -1,4 +1,4 @@
-use crate::ecfft::Ep;
+use crate::ecfft::{generate_coset, Ep, Fp};
 use crate::polynomial::{Coefficients, Polynomial};
 
 use pairing::bn256::{Fq, Fr};
@@ -17,7 +17,17 @@ pub(crate) fn arb_poly_fq(k: usize) -> Polynomial<Fq, Coefficients> {
     )
 }
 
-pub(crate) fn layer_coset(depth: usize) -> Vec<Fq> {
+// random polynomial of n coefficients
+pub(crate) fn arb_poly<F: Field>(n: usize) -> Polynomial<F, Coefficients> {
+    Polynomial::<F, Coefficients>::new((0..n).map(|_| F::random(OsRng)).collect())
+}
+
+pub(crate) fn layer_coset(depth: usize) -> Vec<Fp> {
+    generate_coset(14, depth)
+}
+
+// coset computed by scalar multiplication of each point
+pub(crate) fn naive_layer_coset(depth: usize) -> Vec<Fp> {
     let k = 14;
     let n = 1 << k;
     let step = 1 << depth;
@@ -28,7 +38,7 @@ pub(crate) fn layer_coset(depth: usize) -> Vec<Fq> {
         .into_iter()
         .step_by(step)
         .map(|i| {
-            let coset_point = presentative + acc * Fq::from_raw([i, 0, 0, 0]);
+            let coset_point = presentative + acc * Fp::from_raw([i, 0, 0, 0]);
             coset_point.to_affine().point_projective()
         })
         .collect::<Vec<_>>()
@@ -0,0 +1,63 @@
+// field helpers shared by both ffts and polynomials
+
+use pairing::group::ff::Field;
+use rayon::{current_num_threads, prelude::*};
+
+// invert all non zero elements with one inversion by montgomery trick
+pub(crate) fn batch_inversion<F: Field>(values: &mut [F]) {
+    let mut acc = F::one();
+    let products = values
+        .iter()
+        .map(|value| {
+            let product = acc;
+            if *value != F::zero() {
+                acc *= value;
+            }
+            product
+        })
+        .collect::<Vec<_>>();
+
+    let mut inv = acc.invert().unwrap();
+    values
+        .iter_mut()
+        .zip(products.iter())
+        .rev()
+        .filter(|(value, _)| **value != F::zero())
+        .for_each(|(value, product)| {
+            let tmp = inv * product;
+            inv *= *value;
+            *value = tmp;
+        });
+}
+
+// batch inversion over parallel chunks
+pub(crate) fn parallel_batch_inversion<F: Field>(values: &mut [F]) {
+    let chunk_size = (values.len() / current_num_threads()).max(1);
+    values.par_chunks_mut(chunk_size).for_each(batch_inversion);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{batch_inversion, parallel_batch_inversion};
+    use pairing::bn256::Fq as Fp;
+    use pairing::group::ff::Field;
+    use rand_core::OsRng;
+
+    #[test]
+    fn test_batch_inversion() {
+        let mut values = (0..1000).map(|_| Fp::random(OsRng)).collect::<Vec<_>>();
+        values[0] = Fp::zero();
+        values[500] = Fp::zero();
+        let expected = values
+            .iter()
+            .map(|value| value.invert().unwrap_or(Fp::zero()))
+            .collect::<Vec<_>>();
+        let mut par_values = values.clone();
+
+        batch_inversion(&mut values);
+        parallel_batch_inversion(&mut par_values);
+
+        assert_eq!(values, expected);
+        assert_eq!(par_values, expected);
+    }
+}